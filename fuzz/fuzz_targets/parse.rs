@@ -0,0 +1,15 @@
+#![no_main]
+
+use bowtie::{Diagram, ParseLimits};
+use libfuzzer_sys::fuzz_target;
+
+// Run with `cargo fuzz run parse` from the repository root.
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let limits = ParseLimits::default();
+    if let Ok(diagram) = Diagram::parse_with_limits(input, &limits) {
+        assert!(diagram.components().len() <= limits.max_components);
+    }
+});
@@ -1,221 +1,1715 @@
 //! This module is responsible for drawing on the canvas relying on
 //! a renderer and its context.
-use crate::renderer::{Alignment, Rectangle, Renderer, Vector2};
-use crate::{Component, ComponentKind, Diagram};
-use std::collections::{HashMap, HashSet};
+use crate::diff::{Change, DiagramDiff};
+use crate::renderer::{
+    Alignment, Color, HighlightRenderer, Rectangle, Renderer, ScaledRenderer, Style, Vector2,
+};
+use crate::{
+    BarrierLabelPlacement, BarrierOrder, BarrierState, Component, ComponentAlignment,
+    ComponentKind, Diagram, FontSizes, Options, ScoreThresholds, Side, Spacing, Warning,
+};
+use std::collections::HashSet;
+
+/// Records a layout decision when the `logging` feature is enabled.
+macro_rules! trace_layout {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "logging")]
+        tracing::debug!($($arg)*);
+    };
+}
 
 const COMPONENT_HEIGHT: f64 = 50.0;
 const BARRIER_WIDTH: f64 = 25.0;
 const BARRIER_PADDING_RIGHT: f64 = 10.0;
-const COMPONENT_MARGIN_BOTTOM: f64 = 20.0;
-const COMPONENT_PADDING_X: f64 = 10.0;
 const BARRIER_MARGIN_RIGHT: f64 = 50.0;
 const BARRIERS_CONTAINER_HORIZONTAL_PADDING: f64 = 150.0;
+const RATING_HEIGHT: f64 = 30.0;
+const RATING_MARGIN_BOTTOM: f64 = 10.0;
+const GROUP_SEPARATOR_COLOR: Color = Color::rgb(0xcc, 0xcc, 0xcc);
+const DEGRADED_BARRIER_COLOR: Color = Color::rgb(0xff, 0xbf, 0x00);
+const FAILED_BARRIER_COLOR: Color = Color::rgb(0xf4, 0x43, 0x36);
+const ADDED_COLOR: Color = Color::rgb(0x4c, 0xaf, 0x50);
+const REMOVED_COLOR: Color = Color::rgb(0xf4, 0x43, 0x36);
+const SHARED_BARRIER_COLOR: Color = Color::rgb(0x3f, 0x51, 0xb5);
+const SHARED_BARRIER_TINT: Color = Color::rgb(0xc5, 0xca, 0xe9);
+const ABSENT_BARRIER_COLOR: Color = Color::rgb(0x9e, 0x9e, 0x9e);
+/// Colours of connectors, distinguishable with the common forms of colour
+/// blindness.
+const CONNECTOR_PALETTE: [Color; 6] = [
+    Color::rgb(0x00, 0x72, 0xb2),
+    Color::rgb(0xe6, 0x9f, 0x00),
+    Color::rgb(0x00, 0x9e, 0x73),
+    Color::rgb(0xd5, 0x5e, 0x00),
+    Color::rgb(0x56, 0xb4, 0xe9),
+    Color::rgb(0xcc, 0x79, 0xa7),
+];
+const BORDER_COLOR: Color = Color::rgb(0x9e, 0x9e, 0x9e);
+const BORDER_STROKE_WIDTH: f64 = 1.0;
+const GRID_COLOR: Color = Color::rgb(0xee, 0xee, 0xee);
+const SPINE_COLOR: Color = Color::rgb(0xdd, 0xdd, 0xdd);
+const LOW_RISK_COLOR: Color = Color::rgb(0x4c, 0xaf, 0x50);
+const MEDIUM_RISK_COLOR: Color = Color::rgb(0xff, 0xbf, 0x00);
+const HIGH_RISK_COLOR: Color = Color::rgb(0xf4, 0x43, 0x36);
+const TITLE_MARGIN: f64 = 20.0;
+/// Radius of the event circle when the event may be written below it.
+const CAPTIONED_EVENT_RADIUS: f64 = 30.0;
+const EVENT_CAPTION_MARGIN: f64 = 10.0;
+const DEFAULT_EVENT_PLACEHOLDER: &str = "Top Event";
+const EVENT_PLACEHOLDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+const NOTE_COLOR: Color = Color::rgb(0x44, 0x44, 0x44);
+const NOTE_FONT_SIZE: f64 = 14.0;
+/// Space around the notes, within their footer band.
+const NOTE_MARGIN: f64 = 20.0;
+const CONDITION_COLOR: Color = Color::rgb(0x55, 0x55, 0x55);
+/// Size of a barrier's condition relative to its label.
+const CONDITION_FONT_SCALE: f64 = 0.7;
+const WATERMARK_COLOR: Color = Color::rgb(0xbb, 0xbb, 0xbb);
+const WATERMARK_FONT_SIZE: f64 = 12.0;
+const WATERMARK_MARGIN: f64 = 8.0;
+const CATEGORY_COLOR: Color = Color::rgb(0x66, 0x66, 0x66);
+/// Width reserved on each side for category brackets and their labels.
+const CATEGORY_BAND_WIDTH: f64 = 40.0;
+const CATEGORY_BRACKET_GAP: f64 = 8.0;
+const CATEGORY_BRACKET_TICK: f64 = 6.0;
+/// Size of a category's label relative to the component font.
+const CATEGORY_FONT_SCALE: f64 = 0.8;
+const DESCRIPTION_COLOR: Color = Color::rgb(0x55, 0x55, 0x55);
+/// Size of a component's description relative to its name.
+const DESCRIPTION_FONT_SCALE: f64 = 0.75;
+/// Space around a description, within the body of its box.
+const DESCRIPTION_PADDING: f64 = 8.0;
+/// Width up to which boxes grow to fit their description on one line,
+/// beyond which it wraps.
+const DESCRIPTION_MAX_WIDTH: f64 = 240.0;
+/// Distance between the baselines of wrapped lines, relative to the font size.
+const LINE_SPACING: f64 = 1.2;
+/// Room kept on the first line of a barrier label for its id.
+const BARRIER_ID_ALLOWANCE: &str = "[99] ";
+const ICON_SIZE: f64 = 20.0;
+const ICON_MARGIN: f64 = 6.0;
+/// Width taken from a component's label by its icon.
+const ICON_ALLOWANCE: f64 = ICON_SIZE + ICON_MARGIN;
+const GATE_COLOR: Color = Color::rgb(0x45, 0x5a, 0x64);
+/// Distance between a gate's bracket and the top of the boxes it joins.
+const GATE_OFFSET: f64 = 8.0;
+const GATE_FONT_SIZE: f64 = 10.0;
+const BADGE_COLOR: Color = Color::rgb(0x45, 0x5a, 0x64);
+const BADGE_RADIUS: f64 = 10.0;
+const BADGE_FONT_SIZE: f64 = 12.0;
+const RESIDUAL_COLOR: Color = Color::rgb(0x55, 0x55, 0x55);
+/// Fits the residual likelihood in the margin below a cause.
+const RESIDUAL_FONT_SIZE: f64 = 12.0;
+const COMPONENT_NUMBER_FONT_SCALE: f64 = 0.6;
+const COMPONENT_NUMBER_MARGIN: f64 = 6.0;
+const LEADER_LINE_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+const LEADER_LINE_LENGTH: f64 = 40.0;
+const LEADER_DOT_RADIUS: f64 = 4.0;
+/// Distance of a pathway label from its connector, relative to the font size.
+const PATHWAY_LABEL_OFFSET: f64 = 0.75;
+/// Factor by which fonts shrink at each step of fitting a maximum width.
+const FONT_SHRINK_FACTOR: f64 = 0.9;
+/// Fonts don't shrink below this fraction of their configured size.
+const MIN_FONT_SCALE: f64 = 0.25;
+/// Width of a character relative to the font size, with room to spare.
+const CHARACTER_WIDTH: f64 = 15.0 / 18.0;
 
 pub(crate) struct Brush<'d> {
     context: Context,
     diagram: &'d Diagram,
+    options: Options,
     causes: Vec<&'d Component>,
     consequences: Vec<&'d Component>,
+    /// Changes since a previous version of the diagram, to highlight.
+    diff: Option<&'d DiagramDiff>,
+    /// Ids of the elements to keep when highlighting a pathway, fading the
+    /// rest.
+    highlighted: Option<HashSet<String>>,
 }
 
 /// Holds state variables for rendering purposes.
 struct Context {
     canvas_height: f64,
     canvas_width: f64,
+    content_width: f64,
+    event_centre: Vector2,
     causes_container_height: f64,
     consequences_container_height: f64,
+    component_alignment: ComponentAlignment,
+    spacing: Spacing,
     max_component_box_width: f64,
-    circle_left_point: Option<Vector2>,
-    circle_right_point: Option<Vector2>,
+    /// Height of the component boxes, taller than a header row when they
+    /// have a body for descriptions.
+    component_height: f64,
+    event_radius: f64,
+    mirror: bool,
+    title_band_height: f64,
+    /// Height of the footer band holding the notes.
+    notes_band_height: f64,
+    /// Width reserved outside the components for category brackets.
+    category_band_width: f64,
+    /// Width barrier labels wrap at.
+    barrier_label_width: f64,
+    /// Width of the widest wrapped barrier label, which their rectangles
+    /// are sized to.
+    max_barrier_label_width: f64,
+    barrier_width: f64,
+    barrier_height: f64,
+}
+
+/// Horizontal side of the canvas a kind of component is drawn on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Placement {
+    Left,
+    Right,
 }
 
 impl<'d> Brush<'d> {
-    pub fn render_diagram_into_bytes<R>(r: R, diagram: &'d Diagram) -> Vec<u8>
+    pub fn render_diagram_into_bytes<R>(r: R, diagram: &'d Diagram, options: &Options) -> Vec<u8>
+    where
+        R: Renderer,
+    {
+        Self::render_diagram(r, diagram, options).into_bytes()
+    }
+
+    pub fn render_diagram<R>(r: R, diagram: &'d Diagram, options: &Options) -> R
     where
         R: Renderer,
     {
-        let causes = filter_components(&diagram, ComponentKind::Cause);
-        let consequences = filter_components(&diagram, ComponentKind::Consequence);
+        Brush::new(diagram, options).render(r)
+    }
+
+    /// Computes the layout of a diagram, ready to be rendered any number of
+    /// times.
+    pub fn new(diagram: &'d Diagram, options: &Options) -> Self {
+        let mut causes = sort_by_category(filter_components(diagram, ComponentKind::Cause));
+        let mut consequences =
+            sort_by_category(filter_components(diagram, ComponentKind::Consequence));
+        match options.side {
+            Some(Side::Causes) => consequences.clear(),
+            Some(Side::Consequences) => causes.clear(),
+            None => {}
+        }
+        let options = Options {
+            font_sizes: fit_font_sizes(&causes, &consequences, diagram, options),
+            ..options.clone()
+        };
         let barriers_causes = filter_barriers(&causes);
         let barriers_consequences = filter_barriers(&consequences);
-        let max_component_box_width = calculate_max_components_box_width(&causes, &consequences);
-        let max_barrier_container_width =
-            calculate_max_barriers_container_width(&barriers_causes, &barriers_consequences);
-        let (r, context) = setup_canvas(
-            r,
+        let max_component_box_width = calculate_max_components_box_width(
+            &causes,
+            &consequences,
+            options.font_sizes.component,
+            &options,
+        );
+        let max_barrier_container_width = calculate_max_barriers_container_width(
+            &barriers_causes,
+            &barriers_consequences,
+            &options,
+        );
+        let context = setup_canvas(
             &causes,
             &consequences,
             diagram,
+            &options,
             max_component_box_width,
             max_barrier_container_width,
         );
-        let mut brush = Brush {
+        Brush {
             diagram,
+            options,
             context,
             causes,
             consequences,
+            diff: None,
+            highlighted: None,
+        }
+    }
+
+    /// Highlights the changes of the diagram since a previous version.
+    pub fn with_diff(mut self, diff: &'d DiagramDiff) -> Self {
+        self.diff = Some(diff);
+        self
+    }
+
+    /// Highlights the pathway from a cause through the event to a
+    /// consequence, with the barriers on either side of it, fading the rest.
+    pub fn with_pathway(mut self, cause: &str, consequence: &str) -> Self {
+        let ends = [
+            (cause, ComponentKind::Cause),
+            (consequence, ComponentKind::Consequence),
+        ];
+        let components = ends.into_iter().filter_map(|(name, kind)| {
+            self.diagram
+                .components
+                .iter()
+                .find(|c| c.name == name && c.kind == kind)
+        });
+        let highlighted = components
+            .flat_map(|c| self.get_component_links(c))
+            .collect();
+        self.highlighted = Some(highlighted);
+        self
+    }
+
+    fn get_component_change(&self, component: &Component) -> Option<Change> {
+        self.diff.and_then(|diff| diff.component_change(component))
+    }
+
+    fn get_barrier_change(&self, barrier: &str) -> Option<Change> {
+        self.diff.and_then(|diff| diff.barrier_change(barrier))
+    }
+
+    /// Ids of a component and of its barriers, linking the elements drawn
+    /// for them in interactive outputs.
+    fn get_component_links(&self, component: &Component) -> Vec<String> {
+        let mut links = vec![self.get_component_id(component)];
+        links.extend(component.barriers.iter().map(|b| self.get_barrier_id(b)));
+        links
+    }
+
+    /// Ids of a barrier and of the given components it protects.
+    fn get_barrier_links<'a>(
+        &self,
+        barrier: &str,
+        components: impl IntoIterator<Item = &'a Component>,
+    ) -> Vec<String> {
+        let mut links = vec![self.get_barrier_id(barrier)];
+        links.extend(components.into_iter().map(|c| self.get_component_id(c)));
+        links
+    }
+
+    fn get_component_id(&self, component: &Component) -> String {
+        let position = self
+            .diagram
+            .components
+            .iter()
+            .position(|c| c.name == component.name && c.kind == component.kind);
+        format!("component-{}", position.unwrap_or_default())
+    }
+
+    fn get_barrier_id(&self, barrier: &str) -> String {
+        let position = self.diagram.barriers.iter().position(|b| b.name == barrier);
+        format!("barrier-{}", position.unwrap_or_default())
+    }
+
+    pub fn canvas_width(&self) -> f64 {
+        self.options
+            .canvas_size
+            .map_or(self.context.canvas_width, |(width, _)| width)
+    }
+
+    pub fn canvas_height(&self) -> f64 {
+        self.options
+            .canvas_size
+            .map_or(self.context.canvas_height, |(_, height)| height)
+    }
+
+    /// Reports a canvas larger than [`Options::canvas_size_limit`].
+    pub(crate) fn check_canvas_size(&self, warnings: &mut Vec<Warning>) {
+        let Some((max_width, max_height)) = self.options.canvas_size_limit else {
+            return;
         };
-        brush.render(r)
+        let (width, height) = (self.canvas_width(), self.canvas_height());
+        if width <= max_width && height <= max_height {
+            return;
+        }
+        let message = format!(
+            "canvas of {width:.0}x{height:.0} exceeds the limit of {max_width:.0}x{max_height:.0}; \
+             consider splitting the diagram up or stacking barriers with \
+             `barrier_stack_threshold`"
+        );
+        warnings.push(Warning::new(None, message));
     }
 
-    fn render<R>(&mut self, mut r: R) -> Vec<u8>
+    pub fn render<R>(&self, mut r: R) -> R
     where
         R: Renderer,
     {
-        // Draw a border around the canvas, mostly for debugging purposes.
-        r = r.draw_rectangle(&Rectangle {
-            centre: Vector2 {
-                x: self.context.canvas_width / 2.0,
-                y: self.context.canvas_height / 2.0,
+        r = r
+            .configure(&self.options.renderer_settings)
+            .setup(self.canvas_width(), self.canvas_height());
+        // The canvas is filled, so the diagram doesn't show through to
+        // whatever it is placed on, and outlined only when debugging.
+        let canvas_style = if self.options.debug_border {
+            Style {
+                stroke: Some(BORDER_COLOR),
+                stroke_width: Some(BORDER_STROKE_WIDTH),
+                dashed: true,
+                ..Style::with_class("border")
+            }
+        } else {
+            Style {
+                stroke: Some(Color::WHITE),
+                stroke_width: Some(0.0),
+                ..Style::with_class("background")
+            }
+        };
+        r = r.draw_rectangle(
+            &Rectangle {
+                centre: Vector2 {
+                    x: self.canvas_width() / 2.0,
+                    y: self.canvas_height() / 2.0,
+                },
+                width: self.canvas_width(),
+                height: self.canvas_height(),
             },
-            width: self.context.canvas_width,
-            height: self.context.canvas_height,
-        });
+            &canvas_style,
+        );
+        // A fixed canvas size fits the computed layout in with a transform,
+        // leaving the layout itself untouched.
+        r = match self.options.canvas_size {
+            Some((width, height)) => {
+                let scale = (width / self.context.canvas_width)
+                    .min(height / self.context.canvas_height)
+                    .min(1.0);
+                let offset = Vector2 {
+                    x: (width - self.context.canvas_width * scale) / 2.0,
+                    y: (height - self.context.canvas_height * scale) / 2.0,
+                };
+                let scaled = ScaledRenderer::new(r, scale, offset);
+                self.render_highlighted(scaled).into_inner()
+            }
+            None => self.render_highlighted(r),
+        };
+        self.render_watermark(r).finalize()
+    }
+
+    /// Draws everything laid out by the context.
+    fn render_highlighted<R>(&self, r: R) -> R
+    where
+        R: Renderer,
+    {
+        match &self.highlighted {
+            Some(highlighted) => {
+                let highlight = HighlightRenderer::new(r, highlighted);
+                self.render_content(highlight).into_inner()
+            }
+            None => self.render_content(r),
+        }
+    }
+
+    fn render_content<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        r = self.render_grid(r);
+        r = self.render_spine(r);
+        r = self.render_title(r);
+        r = self.render_notes(r);
         r = self.render_components(r, ComponentKind::Cause);
         r = self.render_components(r, ComponentKind::Consequence);
+        r = self.render_residual_likelihoods(r);
+        r = self.render_component_count_badge(r, ComponentKind::Cause);
+        r = self.render_component_count_badge(r, ComponentKind::Consequence);
+        r = self.render_categories(r, ComponentKind::Cause);
+        r = self.render_categories(r, ComponentKind::Consequence);
         r = self.render_event_circle(r);
+        r = self.render_rating(r);
         r = self.render_barrier_lines(r, ComponentKind::Cause);
         r = self.render_barrier_lines(r, ComponentKind::Consequence);
         r = self.render_barriers(r, ComponentKind::Cause, 0);
         r = self.render_barriers(r, ComponentKind::Consequence, self.causes.len());
-        r.into_bytes()
+        r
+    }
+
+    fn render_watermark<R>(&self, r: R) -> R
+    where
+        R: Renderer,
+    {
+        let parts = [self.options.watermark.clone(), get_timestamp(&self.options)];
+        let parts = parts.into_iter().flatten().collect::<Vec<_>>();
+        if parts.is_empty() {
+            return r;
+        }
+        let text = parts.join(" · ");
+        let font_size = WATERMARK_FONT_SIZE;
+        let width = text_width(&text, font_size);
+        r.draw_text(
+            &text,
+            &Rectangle {
+                centre: Vector2 {
+                    x: self.canvas_width() - WATERMARK_MARGIN - width / 2.0,
+                    y: self.canvas_height() - WATERMARK_MARGIN - font_size / 2.0,
+                },
+                width,
+                height: font_size,
+            },
+            Alignment::Right,
+            &Style {
+                fill: Some(WATERMARK_COLOR),
+                font_size: Some(font_size),
+                ..Style::with_class("watermark")
+            },
+        )
+    }
+
+    fn render_grid<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        let Some(spacing) = self.options.grid_spacing.filter(|s| *s > 0.0) else {
+            return r;
+        };
+        let style = Style {
+            stroke: Some(GRID_COLOR),
+            ..Style::with_class("grid")
+        };
+        let width = self.context.canvas_width;
+        let height = self.context.canvas_height;
+        let mut x = spacing;
+        while x < width {
+            r = r.draw_line(&Vector2 { x, y: 0.0 }, &Vector2 { x, y: height }, &style);
+            x += spacing;
+        }
+        let mut y = spacing;
+        while y < height {
+            r = r.draw_line(&Vector2 { x: 0.0, y }, &Vector2 { x: width, y }, &style);
+            y += spacing;
+        }
+        r
+    }
+
+    /// Draws the vertical line through the event separating the sides.
+    fn render_spine<R>(&self, r: R) -> R
+    where
+        R: Renderer,
+    {
+        if !self.options.spine {
+            return r;
+        }
+        let x = self.context.event_centre.x;
+        r.draw_line(
+            &Vector2 { x, y: 0.0 },
+            &Vector2 {
+                x,
+                y: self.context.canvas_height,
+            },
+            &Style {
+                stroke: Some(self.options.spine_color.unwrap_or(SPINE_COLOR)),
+                ..Style::with_class("spine")
+            },
+        )
+    }
+
+    fn render_title<R>(&self, r: R) -> R
+    where
+        R: Renderer,
+    {
+        if self.diagram.title.is_empty() {
+            return r;
+        }
+        r.draw_text(
+            &self.diagram.title,
+            &Rectangle {
+                centre: Vector2 {
+                    x: self.context.canvas_width / 2.0,
+                    y: self.context.title_band_height / 2.0,
+                },
+                width: self.context.canvas_width,
+                height: self.context.title_band_height,
+            },
+            Alignment::Center,
+            &Style {
+                font_size: Some(self.options.font_sizes.title),
+                ..Style::with_class("title")
+            },
+        )
+    }
+
+    /// Writes the notes in the footer band, left-aligned and wrapped to the
+    /// width of the canvas.
+    fn render_notes<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        let width = self.context.canvas_width - NOTE_MARGIN * 2.0;
+        let mut y = self.context.canvas_height - self.context.notes_band_height
+            + NOTE_MARGIN
+            + NOTE_FONT_SIZE / 2.0;
+        for line in wrap_notes(&self.diagram.notes, width) {
+            r = r.draw_text(
+                &line,
+                &Rectangle {
+                    centre: Vector2 {
+                        x: self.context.canvas_width / 2.0,
+                        y,
+                    },
+                    width,
+                    height: NOTE_FONT_SIZE,
+                },
+                Alignment::Left,
+                &Style {
+                    fill: Some(NOTE_COLOR),
+                    font_size: Some(NOTE_FONT_SIZE),
+                    ..Style::with_class("note")
+                },
+            );
+            y += NOTE_FONT_SIZE * LINE_SPACING;
+        }
+        r
+    }
+
+    fn render_event_circle<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        let radius = self.context.event_radius;
+        let centre = self.context.event_centre;
+        let fill = self.diagram.event_severity.map(get_severity_color);
+        let event = get_event_label(self.diagram, &self.options);
+        // A missing event is drawn as a dashed circle with a faint label,
+        // so that a draft diagram still reads as a bowtie.
+        let is_placeholder = self.diagram.event.is_empty();
+        let as_placeholder = |style: Style| {
+            if !is_placeholder {
+                return style;
+            }
+            Style {
+                fill: Some(EVENT_PLACEHOLDER_COLOR),
+                italic: true,
+                class: Some("event event-placeholder"),
+                ..style
+            }
+        };
+        let circle_style = if is_placeholder {
+            Style {
+                stroke: Some(EVENT_PLACEHOLDER_COLOR),
+                dashed: true,
+                ..Style::with_class("event event-placeholder")
+            }
+        } else {
+            Style {
+                fill,
+                ..Style::with_class("event")
+            }
+        };
+        r = r.draw_circle(radius, &centre, &circle_style);
+        let font_size = self.options.font_sizes.event;
+        if is_event_captioned(self.diagram, font_size, radius, &self.options) {
+            let caption_centre = Vector2 {
+                x: centre.x,
+                y: centre.y + radius + EVENT_CAPTION_MARGIN + font_size / 2.0,
+            };
+            return r.draw_text(
+                event,
+                &Rectangle {
+                    centre: caption_centre,
+                    width: text_width(event, font_size),
+                    height: font_size,
+                },
+                Alignment::Center,
+                &as_placeholder(Style {
+                    font_size: Some(font_size),
+                    ..Style::with_class("event-caption")
+                }),
+            );
+        }
+        // Text too wide for a circle of the diagram's own radius is wrapped
+        // within the square inscribed in it, shrinking if need be.
+        let (lines, font_size) = if text_width(event, font_size) / 2.0 > radius {
+            let side = radius * std::f64::consts::SQRT_2;
+            let square = Rectangle {
+                centre,
+                width: side,
+                height: side,
+            };
+            fit_text(event, &square, font_size)
+        } else {
+            (vec![event.to_owned()], font_size)
+        };
+        let first_line_offset = (lines.len() - 1) as f64 * font_size * LINE_SPACING / 2.0;
+        for (k, line) in lines.iter().enumerate() {
+            let y = centre.y - first_line_offset + k as f64 * font_size * LINE_SPACING;
+            r = r.draw_text(
+                line,
+                &Rectangle {
+                    centre: Vector2 { x: centre.x, y },
+                    width: radius,
+                    height: radius,
+                },
+                Alignment::Center,
+                &as_placeholder(Style {
+                    fill: fill.map(|fill| fill.contrasting_text()),
+                    font_size: Some(font_size),
+                    ..Style::with_class("event")
+                }),
+            );
+        }
+        r
+    }
+
+    /// Renders the risk matrix cell of the event rating above the event circle.
+    fn render_rating<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        let Some(rating) = &self.diagram.rating else {
+            return r;
+        };
+        let label = format!("L{} S{}", rating.likelihood, rating.severity);
+        let radius = self.context.event_radius;
+        let rectangle = Rectangle {
+            centre: Vector2 {
+                x: self.context.event_centre.x,
+                y: self.context.event_centre.y
+                    - radius
+                    - RATING_MARGIN_BOTTOM
+                    - RATING_HEIGHT / 2.0,
+            },
+            width: text_width(&label, self.options.font_sizes.event),
+            height: RATING_HEIGHT,
+        };
+        let fill = rating.color();
+        r = r.draw_rectangle(
+            &rectangle,
+            &Style {
+                fill: Some(fill),
+                ..Style::with_class("rating")
+            },
+        );
+        r.draw_text(
+            &label,
+            &rectangle,
+            Alignment::Center,
+            &Style {
+                fill: Some(fill.contrasting_text()),
+                font_size: Some(self.options.font_sizes.event),
+                ..Style::with_class("rating")
+            },
+        )
+    }
+
+    /// Writes the residual likelihood of each cause that has one just below
+    /// its box, aligned with the outer edge of the box so that it stays on
+    /// the canvas.
+    fn render_residual_likelihoods<R>(&self, mut r: R) -> R
+    where
+        R: Renderer,
+    {
+        if !self.options.show_residual_likelihood {
+            return r;
+        }
+        let kind = ComponentKind::Cause;
+        let alignment = match get_placement(&kind, self.context.mirror) {
+            Placement::Left => Alignment::Left,
+            Placement::Right => Alignment::Right,
+        };
+        for (i, component) in self.causes.iter().enumerate() {
+            let Some(residual) = self.diagram.residual_likelihood(&component.name) else {
+                continue;
+            };
+            let text = format!("residual {residual:.3}");
+            let y = get_component_y_center(i as f64, &kind, &self.context)
+                + self.context.component_height / 2.0
+                + RESIDUAL_FONT_SIZE;
+            r = r.draw_text(
+                &text,
+                &Rectangle {
+                    centre: Vector2 {
+                        x: get_component_x_center(&kind, &self.context),
+                        y,
+                    },
+                    width: self.context.max_component_box_width,
+                    height: RESIDUAL_FONT_SIZE,
+                },
+                alignment,
+                &Style {
+                    fill: Some(RESIDUAL_COLOR),
+                    font_size: Some(RESIDUAL_FONT_SIZE),
+                    links: self.get_component_links(component),
+                    ..Style::with_class("residual-likelihood")
+                },
+            );
+        }
+        r
+    }
+
+    #[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(kind = ?kind)))]
+    fn render_components<R>(&self, mut r: R, kind: ComponentKind) -> R
+    where
+        R: Renderer,
+    {
+        let components = self.get_components(&kind);
+        for (i, component) in components.iter().enumerate().map(|(i, c)| (i as f64, c)) {
+            let y = get_component_y_center(i, &kind, &self.context);
+            let x = get_component_x_center(&kind, &self.context);
+            let rectangle = Rectangle {
+                centre: Vector2 { x, y },
+                width: self.context.max_component_box_width,
+                height: self.context.component_height,
+            };
+            trace_layout!(component = %component.name, x, y, width = rectangle.width);
+            let fill = component
+                .score
+                .map(|s| get_score_color(s, &self.options.score_thresholds));
+            let label = truncate_label(&component.name, self.options.max_label_chars);
+            let style = Style {
+                fill,
+                font_size: Some(self.options.font_sizes.component),
+                tooltip: label.as_ref().map(|_| component.name.clone()),
+                links: self.get_component_links(component),
+                ..Style::with_class("component")
+            };
+            let style = apply_change(
+                style,
+                self.get_component_change(component),
+                ["component component-added", "component component-removed"],
+            );
+            let label = label.as_deref().unwrap_or(&component.name);
+            let number = get_component_number(&kind, i as usize, &self.options);
+            let description = component.description.as_deref();
+            if component.icon.is_none() && number.is_none() && description.is_none() {
+                r = r.draw_text_with_rectangle(label, &rectangle, Alignment::Center, &style);
+                continue;
+            }
+            // The icon sits in the top-left corner and the number in the
+            // top-right one, and the label is centred in the rest of the box,
+            // or of its header if it has a description below.
+            r = r.draw_rectangle(&rectangle.with_padding(2.0), &style);
+            let text_fill = fill.map(|fill| fill.contrasting_text());
+            let top = y - self.context.component_height / 2.0;
+            let mut left = x - rectangle.width / 2.0;
+            let mut right = x + rectangle.width / 2.0;
+            if let Some(icon) = component.icon {
+                let icon_rectangle = Rectangle {
+                    centre: Vector2 {
+                        x: left + ICON_MARGIN + ICON_SIZE / 2.0,
+                        y: top + ICON_MARGIN + ICON_SIZE / 2.0,
+                    },
+                    width: ICON_SIZE,
+                    height: ICON_SIZE,
+                };
+                r = r.draw_icon(
+                    icon,
+                    &icon_rectangle,
+                    &Style {
+                        fill: text_fill,
+                        links: style.links.clone(),
+                        ..Style::with_class("component-icon")
+                    },
+                );
+                left += ICON_ALLOWANCE;
+            }
+            if let Some(number) = &number {
+                let font_size = self.options.font_sizes.component * COMPONENT_NUMBER_FONT_SCALE;
+                let width = text_width(number, font_size);
+                let number_rectangle = Rectangle {
+                    centre: Vector2 {
+                        x: right - COMPONENT_NUMBER_MARGIN - width / 2.0,
+                        y: top + COMPONENT_NUMBER_MARGIN + font_size / 2.0,
+                    },
+                    width,
+                    height: font_size,
+                };
+                r = r.draw_text(
+                    number,
+                    &number_rectangle,
+                    Alignment::Center,
+                    &Style {
+                        fill: text_fill,
+                        font_size: Some(font_size),
+                        links: style.links.clone(),
+                        ..Style::with_class("component-number")
+                    },
+                );
+                right -= width + COMPONENT_NUMBER_MARGIN;
+            }
+            let label_rectangle = Rectangle {
+                centre: Vector2 {
+                    x: (left + right) / 2.0,
+                    y: match description {
+                        Some(_) => top + COMPONENT_HEIGHT / 2.0,
+                        None => y,
+                    },
+                },
+                width: right - left,
+                height: COMPONENT_HEIGHT,
+            };
+            let links = style.links.clone();
+            r = r.draw_text(
+                label,
+                &label_rectangle,
+                Alignment::Center,
+                &Style {
+                    fill: text_fill,
+                    bold: description.is_some(),
+                    ..style
+                },
+            );
+            if let Some(description) = description {
+                r = self.render_component_description(r, &rectangle, description, text_fill, links);
+            }
+        }
+        r
+    }
+
+    /// Writes a component's description in the body of its box, below a
+    /// divider under the header holding its name.
+    fn render_component_description<R>(
+        &self,
+        mut r: R,
+        rectangle: &Rectangle,
+        description: &str,
+        text_fill: Option<Color>,
+        links: Vec<String>,
+    ) -> R
+    where
+        R: Renderer,
+    {
+        let left = rectangle.centre.x - rectangle.width / 2.0;
+        let right = rectangle.centre.x + rectangle.width / 2.0;
+        let divider_y = rectangle.centre.y - rectangle.height / 2.0 + COMPONENT_HEIGHT;
+        r = r.draw_line(
+            &Vector2 {
+                x: left,
+                y: divider_y,
+            },
+            &Vector2 {
+                x: right,
+                y: divider_y,
+            },
+            &Style {
+                links: links.clone(),
+                ..Style::with_class("component-divider")
+            },
+        );
+        let font_size = self.options.font_sizes.component * DESCRIPTION_FONT_SCALE;
+        let width = rectangle.width - DESCRIPTION_PADDING * 2.0;
+        let style = Style {
+            fill: Some(text_fill.unwrap_or(DESCRIPTION_COLOR)),
+            font_size: Some(font_size),
+            links,
+            ..Style::with_class("component-description")
+        };
+        let mut y = divider_y + DESCRIPTION_PADDING + font_size / 2.0;
+        for line in wrap_text(description, width, font_size) {
+            r = r.draw_text(
+                &line,
+                &Rectangle {
+                    centre: Vector2 {
+                        x: rectangle.centre.x,
+                        y,
+                    },
+                    width,
+                    height: font_size,
+                },
+                Alignment::Center,
+                &style,
+            );
+            y += font_size * LINE_SPACING;
+        }
+        r
+    }
+
+    /// Brackets each run of components of the same category on the outer
+    /// side of the components, labelled with the category's name.
+    fn render_categories<R>(&self, mut r: R, kind: ComponentKind) -> R
+    where
+        R: Renderer,
+    {
+        let components = self.get_components(&kind);
+        let placement = get_placement(&kind, self.context.mirror);
+        // Direction pointing away from the event.
+        let outwards = match placement {
+            Placement::Left => -1.0,
+            Placement::Right => 1.0,
+        };
+        let box_edge = get_component_x_center(&kind, &self.context)
+            + outwards * self.context.max_component_box_width / 2.0;
+        let bracket_x = box_edge + outwards * CATEGORY_BRACKET_GAP;
+        let font_size = self.options.font_sizes.component * CATEGORY_FONT_SCALE;
+        let style = Style {
+            stroke: Some(CATEGORY_COLOR),
+            ..Style::with_class("category")
+        };
+        let mut start = 0;
+        while start < components.len() {
+            let category = &components[start].category;
+            let end = start
+                + components[start..]
+                    .iter()
+                    .take_while(|c| &c.category == category)
+                    .count();
+            let Some(category) = category else {
+                start = end;
+                continue;
+            };
+            let top = get_component_y_center(start as f64, &kind, &self.context)
+                - self.context.component_height / 2.0;
+            let bottom = get_component_y_center((end - 1) as f64, &kind, &self.context)
+                + self.context.component_height / 2.0;
+            let tick_x = bracket_x - outwards * CATEGORY_BRACKET_TICK;
+            for (from, to) in [
+                ((tick_x, top), (bracket_x, top)),
+                ((bracket_x, top), (bracket_x, bottom)),
+                ((bracket_x, bottom), (tick_x, bottom)),
+            ] {
+                r = r.draw_line(
+                    &Vector2 {
+                        x: from.0,
+                        y: from.1,
+                    },
+                    &Vector2 { x: to.0, y: to.1 },
+                    &style,
+                );
+            }
+            // The label reads along the bracket, facing away from the event.
+            r = r.draw_text(
+                category,
+                &Rectangle {
+                    centre: Vector2 {
+                        x: bracket_x + outwards * font_size,
+                        y: (top + bottom) / 2.0,
+                    },
+                    width: text_width(category, font_size),
+                    height: font_size,
+                },
+                Alignment::Center,
+                &Style {
+                    font_size: Some(font_size),
+                    rotation: outwards * 90.0,
+                    ..Style::with_class("category")
+                },
+            );
+            start = end;
+        }
+        r
+    }
+
+    fn render_barrier_lines<R>(&self, mut r: R, kind: ComponentKind) -> R
+    where
+        R: Renderer,
+    {
+        let components = self.get_components(&kind);
+        for (i, component) in components.iter().enumerate() {
+            // A connector crossing a failed barrier is drawn dashed.
+            let has_failed_barrier = component.barriers.iter().any(|b| {
+                self.diagram
+                    .find_barrier(b)
+                    .is_some_and(|b| b.state() == BarrierState::Failed)
+            });
+            r = r.draw_polyline(
+                &self.get_connector_points(&kind, i),
+                &apply_change(
+                    Style {
+                        stroke: self
+                            .options
+                            .color_connectors
+                            .then(|| CONNECTOR_PALETTE[i % CONNECTOR_PALETTE.len()]),
+                        stroke_width: self.get_connector_stroke_width(component),
+                        dashed: has_failed_barrier,
+                        line_cap: self.options.connector_line_cap,
+                        line_join: self.options.connector_line_join,
+                        links: self.get_component_links(component),
+                        ..Style::with_class("connector")
+                    },
+                    self.get_component_change(component),
+                    ["connector connector-added", "connector connector-removed"],
+                ),
+            );
+            if let Some(pathway) = &component.pathway {
+                r = self.render_pathway_label(r, &kind, i, component, pathway);
+            }
+        }
+        r
+    }
+
+    /// Returns the stroke width of a cause's connector scaled by its
+    /// likelihood, if enabled.
+    fn get_connector_stroke_width(&self, component: &Component) -> Option<f64> {
+        let (min, max) = self.options.likelihood_connector_widths?;
+        if component.kind != ComponentKind::Cause {
+            return None;
+        }
+        let likelihood = component.likelihood?;
+        Some(min + (max - min) * likelihood)
+    }
+
+    /// Renders the pathway label of the `i`th component along the middle of
+    /// its connector, just above the line.
+    fn render_pathway_label<R>(
+        &self,
+        r: R,
+        kind: &ComponentKind,
+        i: usize,
+        component: &Component,
+        pathway: &str,
+    ) -> R
+    where
+        R: Renderer,
+    {
+        // A routed connector is labelled along its first segment.
+        let points = self.get_connector_points(kind, i);
+        let (from, to) = (points[0], points[1]);
+        let midpoint = get_slope_point(&from, &to, (from.x + to.x) / 2.0);
+        // Measured left to right, so the text is never upside down.
+        let direction = (to.x - from.x).signum();
+        let angle = ((to.y - from.y) * direction).atan2((to.x - from.x).abs());
+        let font_size = self.options.font_sizes.pathway;
+        let offset = font_size * PATHWAY_LABEL_OFFSET;
+        r.draw_text(
+            pathway,
+            &Rectangle {
+                centre: Vector2 {
+                    x: midpoint.x + offset * angle.sin(),
+                    y: midpoint.y - offset * angle.cos(),
+                },
+                width: text_width(pathway, font_size),
+                height: font_size,
+            },
+            Alignment::Center,
+            &Style {
+                font_size: Some(font_size),
+                rotation: angle.to_degrees(),
+                links: self.get_component_links(component),
+                ..Style::with_class("pathway")
+            },
+        )
+    }
+
+    /// Returns the points the connector of the `i`th component passes
+    /// through, from the edge of the component to the event circle.
+    fn get_connector_points(&self, kind: &ComponentKind, i: usize) -> Vec<Vector2> {
+        let mut points = vec![self.get_component_edge(kind, i)];
+        points.extend(self.get_components(kind)[i].route.iter().copied());
+        points.push(self.get_component_circle_point(kind, i));
+        points
+    }
+
+    /// Returns the point of the `i`th component's connector at `x`,
+    /// following its route. Outside the route, the straight line from the
+    /// component to the event is followed instead.
+    fn get_connector_point(&self, kind: &ComponentKind, i: usize, x: f64) -> Vector2 {
+        let points = self.get_connector_points(kind, i);
+        let segment = points.windows(2).find(|segment| {
+            let (from, to) = (segment[0], segment[1]);
+            from.x != to.x && (from.x.min(to.x)..=from.x.max(to.x)).contains(&x)
+        });
+        match segment {
+            Some(segment) => get_slope_point(&segment[0], &segment[1], x),
+            None => get_slope_point(&points[0], &points[points.len() - 1], x),
+        }
+    }
+
+    fn get_component_edge(&self, kind: &ComponentKind, i: usize) -> Vector2 {
+        let y = get_component_y_center(i as f64, &kind, &self.context);
+        let x_center = get_component_x_center(&kind, &self.context);
+        let x_edge = match get_placement(kind, self.context.mirror) {
+            Placement::Left => x_center + self.context.max_component_box_width / 2.0,
+            Placement::Right => x_center - self.context.max_component_box_width / 2.0,
+        };
+        Vector2 { x: x_edge, y }
+    }
+
+    #[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(kind = ?kind)))]
+    fn render_barriers<R>(&self, mut r: R, kind: ComponentKind, id_offset: usize) -> R
+    where
+        R: Renderer,
+    {
+        let components = self.get_components(&kind);
+        let (barriers, central_barriers) = self.get_ordered_barriers(&kind);
+        if is_stacked(components, &self.options) {
+            let barriers = [barriers, central_barriers].concat();
+            return self.render_stacked_barriers(r, &kind, id_offset, &barriers);
+        }
+        let frequencies = get_barrier_frequencies(components);
+        // Centre of each barrier's column, for joining them with gates.
+        let mut columns = Vec::new();
+        let mut previous_group = None;
+        for (i, barrier) in barriers.iter().enumerate() {
+            let x = get_barrier_x_center(i as f64, &kind, &self.context);
+            trace_layout!(barrier = %barrier, column = i, x);
+            let group = self
+                .diagram
+                .find_barrier(barrier)
+                .and_then(|b| b.group.as_deref());
+            if i == 0 || group != previous_group {
+                r = self.render_barrier_group_boundary(r, &kind, i, group);
+            }
+            previous_group = group;
+            let id_y = get_component_y_center(-1.0, &kind, &self.context);
+            let id_centre = Vector2 { x, y: id_y };
+            r = self.render_barrier_labels(r, &kind, id_centre, i, id_offset, barrier);
+            let merged = self.options.merge_barrier_boxes;
+            r = self.render_barrier_boxes(r, &kind, x, barrier, merged);
+            r = self.render_barrier_count_badge(r, id_centre, barrier, &frequencies);
+            columns.push((barrier.as_str(), x));
+        }
+        for (k, barrier) in central_barriers.iter().enumerate() {
+            let x = get_central_barrier_x_center(k as f64, &kind, &self.context);
+            trace_layout!(barrier = %barrier, central_slot = k, x);
+            let i = barriers.len() + k;
+            let id_y = get_component_y_center(-1.0, &kind, &self.context);
+            let id_centre = Vector2 { x, y: id_y };
+            r = self.render_barrier_labels(r, &kind, id_centre, i, id_offset, barrier);
+            r = self.render_barrier_boxes(r, &kind, x, barrier, true);
+            r = self.render_barrier_count_badge(r, id_centre, barrier, &frequencies);
+            columns.push((barrier.as_str(), x));
+        }
+        self.render_gates(r, &kind, &columns)
+    }
+
+    /// Joins the boxes of the barriers in each gate of a component with a
+    /// bracket above them, labelled with the gate's operator.
+    fn render_gates<R>(&self, mut r: R, kind: &ComponentKind, columns: &[(&str, f64)]) -> R
+    where
+        R: Renderer,
+    {
+        for (j, component) in self.get_components(kind).iter().enumerate() {
+            let style = Style {
+                stroke: Some(GATE_COLOR),
+                links: self.get_component_links(component),
+                ..Style::with_class("gate")
+            };
+            for gate in &component.gates {
+                let boxes = gate
+                    .barriers
+                    .iter()
+                    .filter(|b| component.barriers.contains(b))
+                    .filter(|b| !component.associated_barriers.contains(b))
+                    .filter_map(|b| columns.iter().find(|(name, _)| name == b))
+                    .map(|(_, x)| self.get_connector_point(kind, j, *x))
+                    .collect::<Vec<_>>();
+                if boxes.len() < 2 {
+                    continue;
+                }
+                let top = boxes.iter().map(|b| b.y).fold(f64::INFINITY, f64::min);
+                let y = top - self.context.barrier_height / 2.0 - GATE_OFFSET;
+                let left = boxes.iter().map(|b| b.x).fold(f64::INFINITY, f64::min);
+                let right = boxes.iter().map(|b| b.x).fold(f64::NEG_INFINITY, f64::max);
+                r = r.draw_line(&Vector2 { x: left, y }, &Vector2 { x: right, y }, &style);
+                for b in &boxes {
+                    let box_top = Vector2 {
+                        x: b.x,
+                        y: b.y - self.context.barrier_height / 2.0,
+                    };
+                    r = r.draw_line(&Vector2 { x: b.x, y }, &box_top, &style);
+                }
+                let label = gate.operator.as_str();
+                r = r.draw_text_with_rectangle(
+                    label,
+                    &Rectangle {
+                        centre: Vector2 {
+                            x: (left + right) / 2.0,
+                            y,
+                        },
+                        width: text_width(label, GATE_FONT_SIZE),
+                        height: GATE_FONT_SIZE,
+                    },
+                    Alignment::Center,
+                    &Style {
+                        font_size: Some(GATE_FONT_SIZE),
+                        ..style.clone()
+                    },
+                );
+            }
+        }
+        r
+    }
+
+    /// Draws a badge above a barrier column counting the components it
+    /// protects.
+    fn render_barrier_count_badge<R>(
+        &self,
+        r: R,
+        id_centre: Vector2,
+        barrier: &str,
+        frequencies: &[(String, u32)],
+    ) -> R
+    where
+        R: Renderer,
+    {
+        if !self.options.count_badges {
+            return r;
+        }
+        let count = frequencies
+            .iter()
+            .find(|(b, _)| b == barrier)
+            .map_or(0, |(_, count)| *count);
+        let centre = Vector2 {
+            x: id_centre.x,
+            y: id_centre.y - COMPONENT_HEIGHT / 2.0,
+        };
+        render_count_badge(r, centre, count as usize)
+    }
+
+    /// Draws a badge above a side's components counting them.
+    fn render_component_count_badge<R>(&self, r: R, kind: ComponentKind) -> R
+    where
+        R: Renderer,
+    {
+        let components = self.get_components(&kind);
+        if !self.options.count_badges || components.is_empty() {
+            return r;
+        }
+        let centre = Vector2 {
+            x: get_component_x_center(&kind, &self.context),
+            y: get_component_y_center(-1.0, &kind, &self.context) - COMPONENT_HEIGHT / 2.0,
+        };
+        render_count_badge(r, centre, components.len())
+    }
+
+    /// Renders the boxes of a barrier where its column at `x` crosses the
+    /// connectors it protects, except those only associated with it. Merged
+    /// boxes are drawn as a single rectangle spanning all of them.
+    fn render_barrier_boxes<R>(
+        &self,
+        mut r: R,
+        kind: &ComponentKind,
+        x: f64,
+        barrier: &str,
+        merged: bool,
+    ) -> R
+    where
+        R: Renderer,
+    {
+        let crossings = self
+            .get_components(kind)
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.barriers.iter().any(|b| b == barrier))
+            .filter(|(_, c)| !c.associated_barriers.iter().any(|b| b == barrier))
+            .map(|(j, c)| {
+                let y = self.get_connector_point(kind, j, x).y;
+                (*c, y)
+            })
+            .collect::<Vec<_>>();
+        let boxes = if merged && !crossings.is_empty() {
+            let ys = crossings.iter().map(|(_, y)| *y);
+            let top = ys.clone().fold(f64::INFINITY, f64::min);
+            let bottom = ys.fold(f64::NEG_INFINITY, f64::max);
+            let rectangle = Rectangle {
+                centre: Vector2 {
+                    x,
+                    y: (top + bottom) / 2.0,
+                },
+                height: bottom - top + self.context.barrier_height,
+                width: self.context.barrier_width,
+            };
+            let components = crossings.iter().map(|(c, _)| *c);
+            vec![(rectangle, self.get_barrier_links(barrier, components))]
+        } else {
+            crossings
+                .into_iter()
+                .map(|(c, y)| {
+                    let rectangle = Rectangle {
+                        centre: Vector2 { x, y },
+                        height: self.context.barrier_height,
+                        width: self.context.barrier_width,
+                    };
+                    (rectangle, self.get_barrier_links(barrier, [c]))
+                })
+                .collect()
+        };
+        let state = self.get_barrier_state(barrier);
+        let absent = self.is_barrier_absent(barrier);
+        let style = get_barrier_style(state, self.is_highlighted_as_shared(kind, barrier), absent);
+        let style = apply_change(
+            style,
+            self.get_barrier_change(barrier),
+            ["barrier barrier-added", "barrier barrier-removed"],
+        );
+        for (rectangle, links) in boxes {
+            r = r.draw_rectangle(
+                &rectangle,
+                &Style {
+                    links,
+                    ..style.clone()
+                },
+            );
+            if state == BarrierState::Failed && !absent {
+                r = render_cross(r, &rectangle);
+            }
+        }
+        r
+    }
+
+    /// Whether shared barriers are highlighted and the barrier protects more
+    /// than one component of the side.
+    fn is_highlighted_as_shared(&self, kind: &ComponentKind, barrier: &str) -> bool {
+        self.options.highlight_shared_barriers
+            && get_barrier_frequencies(self.get_components(kind))
+                .iter()
+                .any(|(b, frequency)| b == barrier && *frequency > 1)
+    }
+
+    /// Returns the barriers of a side in column order, followed by the
+    /// central barriers, which leave the columns for the slot beside the
+    /// event and are numbered after the others.
+    fn get_ordered_barriers(&self, kind: &ComponentKind) -> (Vec<String>, Vec<String>) {
+        let mut barriers = get_barrier_frequencies(self.get_components(kind))
+            .into_iter()
+            .map(|(barrier, _)| barrier)
+            .collect::<Vec<_>>();
+        if self.options.barrier_order == BarrierOrder::Effectiveness {
+            // Sorting by declaration first keeps ties in that order.
+            barriers.sort_by_key(|b| get_barrier_declaration_order(self.diagram, b));
+            let effectiveness = |b: &String| {
+                self.diagram
+                    .find_barrier(b)
+                    .and_then(|b| b.counted_effectiveness())
+                    .unwrap_or(-1.0)
+            };
+            barriers.sort_by(|a, b| effectiveness(b).total_cmp(&effectiveness(a)));
+        }
+        // Keep barriers of the same group in adjacent columns.
+        barriers.sort_by_key(|b| get_barrier_group_order(self.diagram, b));
+        let (central_barriers, barriers) = barriers
+            .into_iter()
+            .partition(|b| self.diagram.find_barrier(b).is_some_and(|b| b.central));
+        (barriers, central_barriers)
+    }
+
+    /// Returns the top of the `i`th row of the barrier label list, below the
+    /// components. Rows grow to fit wrapped labels.
+    fn get_barrier_label_top(&self, kind: &ComponentKind, i: usize) -> f64 {
+        let components_count = self.get_components(kind).len() as f64;
+        let list_top = get_component_y_center(components_count, kind, &self.context)
+            - self.context.component_height / 2.0;
+        let (barriers, central_barriers) = self.get_ordered_barriers(kind);
+        let font_size = self.options.font_sizes.barrier;
+        let rows_height = barriers
+            .iter()
+            .chain(&central_barriers)
+            .take(i)
+            .map(|b| {
+                let lines = wrap_barrier_label(
+                    b,
+                    self.context.barrier_label_width,
+                    font_size,
+                    self.options.max_label_chars,
+                );
+                calculate_barrier_label_height(lines.len(), font_size)
+                    + self.context.spacing.component_margin
+            })
+            .sum::<f64>();
+        list_top + rows_height
     }
 
-    fn render_event_circle<R>(&mut self, mut r: R) -> R
+    /// Lists the barriers in a vertical panel between the components and the
+    /// event, joined by leader lines to the connectors they sit on. Used when
+    /// the barrier columns would take too much width.
+    fn render_stacked_barriers<R>(
+        &self,
+        mut r: R,
+        kind: &ComponentKind,
+        id_offset: usize,
+        barriers: &[String],
+    ) -> R
     where
         R: Renderer,
     {
-        let radius = calculate_event_circle_radius(&self.diagram.event);
-        r = r.draw_circle(
-            radius,
-            &Vector2 {
-                x: self.context.canvas_width / 2.0,
-                y: self.context.canvas_height / 2.0,
-            },
-        );
-        r = r.draw_text(
-            &self.diagram.event,
-            &Rectangle {
-                centre: Vector2 {
-                    x: self.context.canvas_width / 2.0,
-                    y: self.context.canvas_height / 2.0,
-                },
-                width: radius,
-                height: radius,
-            },
-            Alignment::Center,
+        let components = self.get_components(kind);
+        let x = get_stacked_barriers_x_center(kind, &self.context);
+        // Leader lines run from the inner side of the panel towards the event.
+        let direction = match get_placement(kind, self.context.mirror) {
+            Placement::Left => 1.0,
+            Placement::Right => -1.0,
+        };
+        let margin = self.context.spacing.component_margin;
+        // Rows fit the label beside each box, and the box if it's taller.
+        let row_height = COMPONENT_HEIGHT.max(self.context.barrier_height);
+        let panel_height = calculate_components_container_height_by_count(
+            barriers.len() as f64,
+            row_height,
+            margin,
         );
-        self.context.circle_left_point = Some(Vector2 {
-            x: self.context.canvas_width / 2.0 - radius,
-            y: self.context.canvas_height / 2.0,
-        });
-        self.context.circle_right_point = Some(Vector2 {
-            x: self.context.canvas_width / 2.0 + radius,
-            y: self.context.canvas_height / 2.0,
-        });
+        let panel_top = self.context.event_centre.y - panel_height / 2.0;
+        for (i, barrier) in barriers.iter().enumerate() {
+            let y = panel_top + i as f64 * (row_height + margin) + row_height / 2.0;
+            trace_layout!(barrier = %barrier, row = i, x, y);
+            let rectangle = Rectangle {
+                centre: Vector2 { x, y },
+                height: self.context.barrier_height,
+                width: self.context.barrier_width,
+            };
+            let state = self.get_barrier_state(barrier);
+            let shared = self.is_highlighted_as_shared(kind, barrier);
+            let absent = self.is_barrier_absent(barrier);
+            let style = apply_change(
+                get_barrier_style(state, shared, absent),
+                self.get_barrier_change(barrier),
+                ["barrier barrier-added", "barrier barrier-removed"],
+            );
+            r = r.draw_rectangle(&rectangle, &style);
+            if state == BarrierState::Failed && !absent {
+                r = render_cross(r, &rectangle);
+            }
+            r = self.render_barrier_labels(r, kind, rectangle.centre, i, id_offset, barrier);
+            let leader_start = Vector2 {
+                x: x + direction * self.context.barrier_width / 2.0,
+                y,
+            };
+            let leader_x = leader_start.x + direction * LEADER_LINE_LENGTH;
+            let barrier_components = components
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.barriers.contains(barrier));
+            for (j, _) in barrier_components {
+                let leader_end = self.get_connector_point(kind, j, leader_x);
+                let style = Style {
+                    fill: Some(LEADER_LINE_COLOR),
+                    stroke: Some(LEADER_LINE_COLOR),
+                    ..Style::with_class("barrier-leader")
+                };
+                r = r.draw_line(&leader_start, &leader_end, &style);
+                r = r.draw_circle(LEADER_DOT_RADIUS, &leader_end, &style);
+            }
+        }
         r
     }
 
-    fn render_components<R>(&mut self, mut r: R, kind: ComponentKind) -> R
+    /// Renders the id of the barrier at index `i` at `id_centre`, and its
+    /// numbered name in the label list below the components.
+    fn render_barrier_labels<R>(
+        &self,
+        mut r: R,
+        kind: &ComponentKind,
+        id_centre: Vector2,
+        i: usize,
+        id_offset: usize,
+        barrier: &str,
+    ) -> R
     where
         R: Renderer,
     {
-        let components = self.get_components(&kind);
-        for (i, component) in components.iter().enumerate().map(|(i, c)| (i as f64, c)) {
-            let y = get_component_y_center(i, &kind, &self.context);
-            let x = get_component_x_center(&kind, &self.context);
-            let rectangle = Rectangle {
-                centre: Vector2 { x, y },
-                width: self.context.max_component_box_width,
+        let label_id = format!("{}", id_offset + i + 1);
+        let protected = self
+            .get_components(kind)
+            .iter()
+            .filter(|c| c.barriers.iter().any(|b| b == barrier))
+            .copied();
+        let links = self.get_barrier_links(barrier, protected);
+        r = r.draw_text(
+            &label_id,
+            &Rectangle {
+                centre: id_centre,
                 height: COMPONENT_HEIGHT,
+                width: self.context.barrier_width,
+            },
+            Alignment::Center,
+            &Style {
+                font_size: Some(self.options.font_sizes.barrier),
+                links: links.clone(),
+                ..Style::with_class("barrier-id")
+            },
+        );
+        if has_column_labels(self.get_components(kind), &self.options) {
+            return self.render_barrier_column_label(r, kind, id_centre.x, barrier, links);
+        }
+        // Render barrier label, wrapped from the outer edge of the components
+        // towards the event. The id goes on the first line.
+        let placement = get_placement(kind, self.context.mirror);
+        let font_size = self.options.font_sizes.barrier;
+        let wrap_width = self.context.barrier_label_width;
+        let width = self.context.max_barrier_label_width;
+        let outer_edge = match placement {
+            Placement::Left => {
+                get_component_x_center(kind, &self.context)
+                    - self.context.max_component_box_width / 2.0
+            }
+            Placement::Right => {
+                get_component_x_center(kind, &self.context)
+                    + self.context.max_component_box_width / 2.0
+            }
+        };
+        let label_x = match placement {
+            Placement::Left => outer_edge + width / 2.0,
+            Placement::Right => outer_edge - width / 2.0,
+        };
+        let alignment = get_barrier_label_alignment(placement);
+        let mut label_rectangle = Rectangle {
+            centre: Vector2 {
+                y: self.get_barrier_label_top(kind, i) + COMPONENT_HEIGHT / 2.0,
+                x: label_x,
+            },
+            width,
+            height: COMPONENT_HEIGHT,
+        };
+        let max_label_chars = self.options.max_label_chars;
+        let tooltip = truncate_label(barrier, max_label_chars).map(|_| barrier.to_owned());
+        let lines = wrap_barrier_label(barrier, wrap_width, font_size, max_label_chars);
+        for (j, line) in lines.iter().enumerate() {
+            let text = if j == 0 {
+                get_barrier_label(placement, &label_id, line)
+            } else {
+                line.clone()
             };
-            r = r.draw_text_with_rectangle(&component.name, &rectangle, Alignment::Center);
+            if j > 0 {
+                label_rectangle.centre.y += font_size * LINE_SPACING;
+            }
+            r = r.draw_text(
+                &text,
+                &label_rectangle,
+                alignment,
+                &Style {
+                    font_size: Some(font_size),
+                    tooltip: tooltip.clone(),
+                    links: links.clone(),
+                    ..Style::with_class("barrier-label")
+                },
+            );
         }
-        r
+        let Some(condition) = self
+            .diagram
+            .find_barrier(barrier)
+            .and_then(|b| b.condition.as_deref())
+        else {
+            return r;
+        };
+        // Render the condition as subtext below the label.
+        let font_size = self.options.font_sizes.barrier * CONDITION_FONT_SCALE;
+        let mut condition_rectangle = label_rectangle;
+        condition_rectangle.centre.y += self.options.font_sizes.barrier;
+        r.draw_text(
+            &format!("if {condition}"),
+            &condition_rectangle,
+            alignment,
+            &Style {
+                font_size: Some(font_size),
+                italic: true,
+                fill: Some(CONDITION_COLOR),
+                ..Style::with_class("barrier-condition")
+            },
+        )
     }
 
-    fn render_barrier_lines<R>(&mut self, mut r: R, kind: ComponentKind) -> R
+    /// Renders the name of a barrier down its column, starting below the
+    /// components, with its condition alongside.
+    fn render_barrier_column_label<R>(
+        &self,
+        r: R,
+        kind: &ComponentKind,
+        x: f64,
+        barrier: &str,
+        links: Vec<String>,
+    ) -> R
     where
         R: Renderer,
     {
-        let components = self.get_components(&kind);
-        let circle_point = self.get_component_circle_point(&kind);
-        for (i, _) in components.into_iter().enumerate() {
-            r = r.draw_line(&self.get_component_edge(&kind, i), &circle_point);
-        }
-        r
+        let font_size = self.options.font_sizes.barrier;
+        let label = truncate_label(barrier, self.options.max_label_chars);
+        let tooltip = label.as_ref().map(|_| barrier.to_owned());
+        let text = label.as_deref().unwrap_or(barrier);
+        let top = self.get_barrier_label_top(kind, 0);
+        // The text is laid out horizontally and turned clockwise about the
+        // centre of its containment, so that its start ends up on top.
+        let length = text_width(text, font_size);
+        let mut containment = Rectangle {
+            centre: Vector2 {
+                x,
+                y: top + length / 2.0,
+            },
+            width: length,
+            height: font_size,
+        };
+        let r = r.draw_text(
+            text,
+            &containment,
+            Alignment::Left,
+            &Style {
+                font_size: Some(font_size),
+                tooltip,
+                links,
+                rotation: 90.0,
+                ..Style::with_class("barrier-label")
+            },
+        );
+        let Some(condition) = self
+            .diagram
+            .find_barrier(barrier)
+            .and_then(|b| b.condition.as_deref())
+        else {
+            return r;
+        };
+        // Below the label once turned, i.e. to its left.
+        let condition = format!("if {condition}");
+        let condition_font_size = font_size * CONDITION_FONT_SCALE;
+        let length = text_width(&condition, condition_font_size);
+        containment.centre.x -= font_size;
+        containment.centre.y = top + length / 2.0;
+        containment.width = length;
+        r.draw_text(
+            &condition,
+            &containment,
+            Alignment::Left,
+            &Style {
+                font_size: Some(condition_font_size),
+                italic: true,
+                fill: Some(CONDITION_COLOR),
+                rotation: 90.0,
+                ..Style::with_class("barrier-condition")
+            },
+        )
     }
 
-    fn get_component_edge(&self, kind: &ComponentKind, i: usize) -> Vector2 {
-        let y = get_component_y_center(i as f64, &kind, &self.context);
-        let x_center = get_component_x_center(&kind, &self.context);
-        let x_edge = match kind {
-            ComponentKind::Cause => x_center + self.context.max_component_box_width / 2.0,
-            ComponentKind::Consequence => x_center - self.context.max_component_box_width / 2.0,
-        };
-        Vector2 { x: x_edge, y }
+    fn get_barrier_state(&self, barrier: &str) -> BarrierState {
+        self.diagram
+            .find_barrier(barrier)
+            .map(|b| b.state())
+            .unwrap_or_default()
+    }
+
+    fn is_barrier_absent(&self, barrier: &str) -> bool {
+        self.diagram.find_barrier(barrier).is_some_and(|b| b.absent)
     }
 
-    fn render_barriers<R>(&mut self, mut r: R, kind: ComponentKind, id_offset: usize) -> R
+    /// Renders the header of a barrier group starting at column `i`, and a
+    /// separator from the previous column if there is one.
+    fn render_barrier_group_boundary<R>(
+        &self,
+        mut r: R,
+        kind: &ComponentKind,
+        i: usize,
+        group: Option<&str>,
+    ) -> R
     where
         R: Renderer,
     {
-        let components = self.get_components(&kind);
-        let circle_point = self.get_component_circle_point(&kind);
-        let barrier_frequencies = get_barrier_frequencies(components)
-            .into_iter()
-            .map(|(barrier, _)| barrier);
-        for (i, barrier) in barrier_frequencies.enumerate() {
-            let x = get_barrier_x_center(i as f64, &kind, &self.context);
-            let label_id = format!("{}", id_offset + i + 1);
+        let x = get_barrier_x_center(i as f64, kind, &self.context);
+        if let Some(group) = group {
+            let font_size = self.options.font_sizes.barrier;
+            let width = text_width(group, font_size);
             r = r.draw_text(
-                &label_id,
+                group,
                 &Rectangle {
                     centre: Vector2 {
-                        x,
-                        y: get_component_y_center(-1.0, &kind, &self.context),
+                        x: x + (width - self.context.barrier_width) / 2.0,
+                        y: get_component_y_center(-2.0, kind, &self.context),
                     },
                     height: COMPONENT_HEIGHT,
-                    width: BARRIER_WIDTH,
+                    width,
                 },
-                Alignment::Center,
-            );
-            let barrier_components = components.iter().enumerate().filter_map(|(j, c)| {
-                if c.barriers.contains(&barrier) {
-                    Some((j, c))
-                } else {
-                    None
-                }
-            });
-            // Render barrier label.
-            let label_y =
-                get_component_y_center((components.len() + i) as f64, &kind, &self.context);
-            let label_x = get_component_x_center(&kind, &self.context);
-            r = r.draw_text(
-                &get_barrier_label(&kind, &label_id, &barrier),
-                &Rectangle {
-                    centre: Vector2 {
-                        y: label_y,
-                        x: label_x,
-                    },
-                    width: self.context.max_component_box_width,
-                    height: COMPONENT_HEIGHT,
+                Alignment::Left,
+                &Style {
+                    font_size: Some(font_size),
+                    ..Style::with_class("barrier-group")
                 },
-                get_barrier_label_alignment(&kind),
             );
-            for (j, _) in barrier_components {
-                let barrier_point =
-                    get_slope_point(&self.get_component_edge(&kind, j), &circle_point, x);
-                // Render barrier rectangle.
-                r = r.draw_rectangle(&Rectangle {
-                    centre: barrier_point,
-                    height: COMPONENT_HEIGHT,
-                    width: BARRIER_WIDTH,
-                });
-            }
         }
-        r
+        if i == 0 {
+            return r;
+        }
+        let previous_x = get_barrier_x_center((i - 1) as f64, kind, &self.context);
+        let separator_x = (x + previous_x) / 2.0;
+        let components_count = self.get_components(kind).len() as f64;
+        let top = get_component_y_center(-1.0, kind, &self.context) - COMPONENT_HEIGHT / 2.0;
+        let bottom = get_component_y_center(components_count - 1.0, kind, &self.context)
+            + self.context.component_height / 2.0;
+        r.draw_line(
+            &Vector2 {
+                x: separator_x,
+                y: top,
+            },
+            &Vector2 {
+                x: separator_x,
+                y: bottom,
+            },
+            &Style {
+                stroke: Some(GROUP_SEPARATOR_COLOR),
+                ..Style::with_class("barrier-group")
+            },
+        )
     }
 
     fn get_components(&self, kind: &ComponentKind) -> &[&Component] {
@@ -225,182 +1719,731 @@ impl<'d> Brush<'d> {
         }
     }
 
-    fn get_component_circle_point(&self, kind: &ComponentKind) -> Vector2 {
-        match kind {
-            ComponentKind::Cause => self.context.circle_left_point.unwrap().clone(),
-            ComponentKind::Consequence => self.context.circle_right_point.unwrap().clone(),
+    /// Returns where the connector of the `i`th component attaches to the
+    /// event circle. Connectors fan out along the circle's arc towards their
+    /// component by the configured spread, or meet at the side of the circle
+    /// when the spread is zero.
+    fn get_component_circle_point(&self, kind: &ComponentKind, i: usize) -> Vector2 {
+        let centre = self.context.event_centre;
+        let radius = self.context.event_radius;
+        let edge = self.get_component_edge(kind, i);
+        let direction = match get_placement(kind, self.context.mirror) {
+            Placement::Left => -1.0,
+            Placement::Right => 1.0,
+        };
+        let angle = (edge.y - centre.y).atan2((edge.x - centre.x).abs());
+        let angle = angle * self.options.connector_spread.clamp(0.0, 1.0);
+        Vector2 {
+            x: centre.x + direction * radius * angle.cos(),
+            y: centre.y + radius * angle.sin(),
         }
     }
 }
 
+/// Draws a small circle holding a count.
+fn render_count_badge<R>(mut r: R, centre: Vector2, count: usize) -> R
+where
+    R: Renderer,
+{
+    let style = Style {
+        fill: Some(BADGE_COLOR),
+        stroke: Some(BADGE_COLOR),
+        ..Style::with_class("count-badge")
+    };
+    r = r.draw_circle(BADGE_RADIUS, &centre, &style);
+    r.draw_text(
+        &count.to_string(),
+        &Rectangle {
+            centre,
+            width: BADGE_RADIUS * 2.0,
+            height: BADGE_RADIUS * 2.0,
+        },
+        Alignment::Center,
+        &Style {
+            fill: Some(BADGE_COLOR.contrasting_text()),
+            font_size: Some(BADGE_FONT_SIZE),
+            ..Style::with_class("count-badge")
+        },
+    )
+}
+
+/// Counts the components each barrier protects, most frequent first. Ties
+/// keep the order barriers first appear in, so that repeated calls agree.
 fn get_barrier_frequencies(components: &[&Component]) -> Vec<(String, u32)> {
-    let mut frequencies = HashMap::new();
+    let mut frequencies: Vec<(String, u32)> = Vec::new();
     for component in components {
         for barrier in &component.barriers {
-            let frequency = frequencies.entry(barrier.clone()).or_insert(0);
-            *frequency += 1;
+            match frequencies.iter_mut().find(|(b, _)| b == barrier) {
+                Some((_, frequency)) => *frequency += 1,
+                None => frequencies.push((barrier.clone(), 1)),
+            }
         }
     }
-    let mut frequencies = frequencies.into_iter().collect::<Vec<(_, _)>>();
     frequencies.sort_by(|a, b| a.1.cmp(&b.1).reverse());
     frequencies
 }
 
-fn filter_components(diagram: &Diagram, kind: ComponentKind) -> Vec<&Component> {
+fn get_barrier_declaration_order(diagram: &Diagram, barrier: &str) -> usize {
+    diagram
+        .barriers
+        .iter()
+        .position(|b| b.name == barrier)
+        .unwrap_or(usize::MAX)
+}
+
+/// Orders barrier groups by their first declaration, with ungrouped
+/// barriers last.
+fn get_barrier_group_order(diagram: &Diagram, barrier: &str) -> usize {
+    let Some(group) = diagram.find_barrier(barrier).and_then(|b| b.group.as_ref()) else {
+        return usize::MAX;
+    };
     diagram
-        .components
+        .barriers
+        .iter()
+        .position(|b| b.group.as_ref() == Some(group))
+        .unwrap_or(usize::MAX)
+}
+
+/// Keeps components of the same category in adjacent rows, ordered by the
+/// first component of each category, with uncategorised components last.
+fn sort_by_category(components: Vec<&Component>) -> Vec<&Component> {
+    let order = components
+        .iter()
+        .map(|c| {
+            let Some(category) = &c.category else {
+                return usize::MAX;
+            };
+            components
+                .iter()
+                .position(|other| other.category.as_ref() == Some(category))
+                .unwrap_or(usize::MAX)
+        })
+        .collect::<Vec<_>>();
+    let mut ordered = order.into_iter().zip(components).collect::<Vec<_>>();
+    ordered.sort_by_key(|(order, _)| *order);
+    ordered.into_iter().map(|(_, c)| c).collect()
+}
+
+fn calculate_category_band_width(causes: &[&Component], consequences: &[&Component]) -> f64 {
+    let has_categories = causes
         .iter()
-        .filter(|c| c.kind == kind)
-        .collect::<Vec<&Component>>()
+        .chain(consequences)
+        .any(|c| c.category.is_some());
+    if has_categories {
+        CATEGORY_BAND_WIDTH
+    } else {
+        0.0
+    }
+}
+
+pub(crate) fn filter_components(diagram: &Diagram, kind: ComponentKind) -> Vec<&Component> {
+    diagram.components_of(kind).collect()
 }
 
-fn filter_barriers<'a>(components: &'a [&Component]) -> HashSet<&'a str> {
-    let mut barriers = HashSet::<&str>::new();
+/// Returns the distinct barriers of the components, in the order they are
+/// first attached, so that anything iterating them is deterministic.
+pub(crate) fn filter_barriers<'a>(components: &'a [&Component]) -> Vec<&'a str> {
+    let mut seen = HashSet::<&str>::new();
+    let mut barriers = Vec::<&str>::new();
     for component in components {
         for component_barrier in &component.barriers {
-            barriers.insert(component_barrier);
+            if seen.insert(component_barrier) {
+                barriers.push(component_barrier);
+            }
         }
     }
     barriers
 }
 
-fn calculate_event_circle_radius(event: &str) -> f64 {
-    let width = text_width(&event);
-    width / 2.0
+/// Returns the radius of the event circle: the diagram's own, or else one
+/// fitting the event's text.
+fn calculate_event_circle_radius(diagram: &Diagram, font_size: f64, options: &Options) -> f64 {
+    if let Some(radius) = diagram.event_radius {
+        return radius;
+    }
+    let event = get_event_label(diagram, options);
+    if options.event_caption {
+        return CAPTIONED_EVENT_RADIUS.max(options.min_event_radius);
+    }
+    let width = text_width(event, font_size);
+    (width / 2.0).max(options.min_event_radius)
+}
+
+/// Returns the text of the event, or the placeholder drawn instead of a
+/// missing one.
+fn get_event_label<'a>(diagram: &'a Diagram, options: &'a Options) -> &'a str {
+    if !diagram.event.is_empty() {
+        return &diagram.event;
+    }
+    options
+        .event_placeholder
+        .as_deref()
+        .unwrap_or(DEFAULT_EVENT_PLACEHOLDER)
+}
+
+/// Whether the event is written below its circle, not fitting inside. An
+/// event that doesn't fit a circle of the diagram's own radius is wrapped
+/// inside it instead, unless events are captioned.
+fn is_event_captioned(diagram: &Diagram, font_size: f64, radius: f64, options: &Options) -> bool {
+    let event = get_event_label(diagram, options);
+    let is_wrapped = diagram.event_radius.is_some() && !options.event_caption;
+    text_width(event, font_size) / 2.0 > radius && !is_wrapped
 }
 
-fn setup_canvas<'a, R>(
-    r: R,
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all))]
+fn setup_canvas(
     causes: &[&Component],
     consequences: &[&Component],
     diagram: &Diagram,
+    options: &Options,
     max_component_box_width: f64,
     max_barriers_container_width: f64,
-) -> (R, Context)
-where
-    R: Renderer,
-{
-    let causes_container_height = calculate_components_container_height(causes);
-    let consequences_container_height = calculate_components_container_height(consequences);
-    let max_barriers_height =
-        calculate_barriers_height(causes) + calculate_barriers_height(consequences);
-    let max_container_height =
-        causes_container_height.max(consequences_container_height) + max_barriers_height;
-    let canvas_height = max_container_height * 1.1 + 150.0;
-    let canvas_width = calculate_canvas_width(
-        diagram,
+) -> Context {
+    let radius = calculate_event_circle_radius(diagram, options.font_sizes.event, options);
+    let category_band_width = calculate_category_band_width(causes, consequences);
+    let full_width = calculate_canvas_width(
+        radius,
         max_component_box_width,
         max_barriers_container_width,
+    ) + category_band_width * 2.0;
+    // Barrier labels may run from the outer edge of the components up to
+    // the event circle.
+    let spacing = options.spacing;
+    let barrier_label_width =
+        full_width / 2.0 - radius - category_band_width - spacing.component_padding;
+    let component_height = calculate_component_height(
+        &[causes, consequences].concat(),
+        max_component_box_width,
+        options.font_sizes.component,
+    );
+    let causes_container_height =
+        calculate_components_container_height(causes, component_height, spacing.component_margin);
+    let consequences_container_height = calculate_components_container_height(
+        consequences,
+        component_height,
+        spacing.component_margin,
     );
+    let barrier_font_size = options.font_sizes.barrier;
+    let max_barrier_label_width = calculate_max_barrier_label_width(
+        &[causes, consequences].concat(),
+        barrier_label_width,
+        barrier_font_size,
+        options,
+    );
+    let max_barriers_height =
+        calculate_barriers_height(causes, barrier_label_width, barrier_font_size, options)
+            + calculate_barriers_height(
+                consequences,
+                barrier_label_width,
+                barrier_font_size,
+                options,
+            );
+    let max_container_height =
+        causes_container_height.max(consequences_container_height) + max_barriers_height;
+    // The event is centred vertically, so room for its caption below is
+    // reserved above it too.
+    let caption_height = if is_event_captioned(diagram, options.font_sizes.event, radius, options) {
+        options.font_sizes.event + EVENT_CAPTION_MARGIN
+    } else {
+        0.0
+    };
+    let content_height = max_container_height * 1.1 + 150.0 + caption_height * 2.0;
+    let (content_width, event_x) = calculate_content_width(full_width, radius, options);
+    let title_band_height = if diagram.title.is_empty() {
+        0.0
+    } else {
+        options.font_sizes.title + TITLE_MARGIN * 2.0
+    };
+    let title_width = text_width(&diagram.title, options.font_sizes.title);
+    let canvas_width = content_width.max(title_width).max(options.min_canvas_width);
+    let note_lines = wrap_notes(&diagram.notes, canvas_width - NOTE_MARGIN * 2.0).len();
+    let notes_band_height = if note_lines == 0 {
+        0.0
+    } else {
+        note_lines as f64 * NOTE_FONT_SIZE * LINE_SPACING + NOTE_MARGIN * 2.0
+    };
+    // Content is laid out around the centre of the canvas between the title
+    // and the notes, so a canvas grown to the minimum size keeps it centered.
+    let canvas_height =
+        (content_height + title_band_height + notes_band_height).max(options.min_canvas_height);
+    let content_left = (canvas_width - content_width) / 2.0;
+    let (barrier_width, barrier_height) = get_barrier_box_size(options);
     let canvas = Context {
         canvas_height,
         canvas_width,
+        content_width,
+        event_centre: Vector2 {
+            x: content_left + event_x,
+            y: title_band_height + (canvas_height - title_band_height - notes_band_height) / 2.0,
+        },
         causes_container_height,
         consequences_container_height,
+        component_alignment: options.component_alignment,
+        spacing: options.spacing,
         max_component_box_width,
-        circle_left_point: None,
-        circle_right_point: None,
+        component_height,
+        event_radius: radius,
+        mirror: options.mirror,
+        title_band_height,
+        notes_band_height,
+        category_band_width,
+        barrier_label_width,
+        max_barrier_label_width,
+        barrier_width,
+        barrier_height,
+    };
+    trace_layout!(
+        canvas_width = canvas.canvas_width,
+        canvas_height = canvas.canvas_height,
+        content_width,
+        content_height,
+        event_x = canvas.event_centre.x,
+        event_y = canvas.event_centre.y,
+        event_radius = radius,
+        max_component_box_width,
+        max_barriers_container_width,
+        "canvas laid out"
+    );
+    canvas
+}
+
+/// Returns the width of the content and the event's offset within it. A
+/// single side keeps its half of the layout plus room for the event circle,
+/// which stays on the inner edge as the anchor.
+fn calculate_content_width(full_width: f64, radius: f64, options: &Options) -> (f64, f64) {
+    let kept_placement = options.side.map(|side| {
+        let kind = match side {
+            Side::Causes => ComponentKind::Cause,
+            Side::Consequences => ComponentKind::Consequence,
+        };
+        get_placement(&kind, options.mirror)
+    });
+    match kept_placement {
+        None => (full_width, full_width / 2.0),
+        Some(Placement::Left) => (full_width / 2.0 + radius, full_width / 2.0),
+        Some(Placement::Right) => (full_width / 2.0 + radius, radius),
+    }
+}
+
+/// Shrinks the configured font sizes step by step until the canvas fits the
+/// maximum width, if there is one.
+fn fit_font_sizes(
+    causes: &[&Component],
+    consequences: &[&Component],
+    diagram: &Diagram,
+    options: &Options,
+) -> FontSizes {
+    let Some(max_canvas_width) = options.max_canvas_width else {
+        return options.font_sizes;
     };
-    let r = r.setup(canvas.canvas_width, canvas.canvas_height);
-    (r, canvas)
+    let max_barriers_container_width = calculate_max_barriers_container_width(
+        &filter_barriers(causes),
+        &filter_barriers(consequences),
+        options,
+    );
+    let mut scale = 1.0;
+    loop {
+        let font_sizes = options.font_sizes.scaled(scale);
+        let max_component_box_width =
+            calculate_max_components_box_width(causes, consequences, font_sizes.component, options);
+        let radius = calculate_event_circle_radius(diagram, font_sizes.event, options);
+        let full_width = calculate_canvas_width(
+            radius,
+            max_component_box_width,
+            max_barriers_container_width,
+        ) + calculate_category_band_width(causes, consequences) * 2.0;
+        let (content_width, _) = calculate_content_width(full_width, radius, options);
+        let width = content_width.max(text_width(&diagram.title, font_sizes.title));
+        if width <= max_canvas_width || scale * FONT_SHRINK_FACTOR < MIN_FONT_SCALE {
+            return font_sizes;
+        }
+        scale *= FONT_SHRINK_FACTOR;
+    }
 }
 
-fn calculate_components_container_height(components: &[&Component]) -> f64 {
+fn calculate_components_container_height(
+    components: &[&Component],
+    height: f64,
+    margin: f64,
+) -> f64 {
     let components_count = components.len() as f64;
-    calculate_components_container_height_by_count(components_count)
+    calculate_components_container_height_by_count(components_count, height, margin)
+}
+
+fn calculate_components_container_height_by_count(
+    components_count: f64,
+    height: f64,
+    margin: f64,
+) -> f64 {
+    components_count * height + ((components_count - 1.0) * margin)
+}
+
+/// Returns the height of the component boxes: a header row for the name,
+/// and if any component has a description, a body fitting the longest one
+/// wrapped to the width of the boxes.
+fn calculate_component_height(components: &[&Component], box_width: f64, font_size: f64) -> f64 {
+    let font_size = font_size * DESCRIPTION_FONT_SCALE;
+    let width = box_width - DESCRIPTION_PADDING * 2.0;
+    let lines = components
+        .iter()
+        .filter_map(|c| c.description.as_deref())
+        .map(|description| wrap_text(description, width, font_size).len())
+        .max();
+    match lines {
+        Some(lines) => {
+            COMPONENT_HEIGHT + lines as f64 * font_size * LINE_SPACING + DESCRIPTION_PADDING * 2.0
+        }
+        None => COMPONENT_HEIGHT,
+    }
+}
+
+/// Returns the height of the barrier label list of a side.
+fn calculate_barriers_height(
+    components: &[&Component],
+    label_width: f64,
+    font_size: f64,
+    options: &Options,
+) -> f64 {
+    let barriers = filter_barriers(components);
+    if has_column_labels(components, options) {
+        return barriers
+            .iter()
+            .map(|b| {
+                let label = truncate_label(b, options.max_label_chars);
+                text_width(label.as_deref().unwrap_or(b), font_size)
+            })
+            .fold(0.0, f64::max);
+    }
+    let rows_height = barriers
+        .iter()
+        .map(|b| {
+            let lines = wrap_barrier_label(b, label_width, font_size, options.max_label_chars);
+            calculate_barrier_label_height(lines.len(), font_size)
+        })
+        .sum::<f64>();
+    rows_height + (barriers.len() as f64 - 1.0) * options.spacing.component_margin
+}
+
+/// Returns the width of the widest barrier label once wrapped at
+/// `label_width`, including room for its id.
+fn calculate_max_barrier_label_width(
+    components: &[&Component],
+    label_width: f64,
+    font_size: f64,
+    options: &Options,
+) -> f64 {
+    filter_barriers(components)
+        .iter()
+        .flat_map(|b| wrap_barrier_label(b, label_width, font_size, options.max_label_chars))
+        .map(|line| text_width(&line, font_size))
+        .fold(0.0, f64::max)
+        + text_width(BARRIER_ID_ALLOWANCE, font_size)
 }
 
-fn calculate_components_container_height_by_count(components_count: f64) -> f64 {
-    components_count * COMPONENT_HEIGHT + ((components_count - 1.0) * COMPONENT_MARGIN_BOTTOM)
+/// Returns the height of a row of the barrier label list.
+fn calculate_barrier_label_height(lines_count: usize, font_size: f64) -> f64 {
+    COMPONENT_HEIGHT + lines_count.saturating_sub(1) as f64 * font_size * LINE_SPACING
 }
 
-fn calculate_barriers_height(components: &[&Component]) -> f64 {
-    let barriers = components
+/// Wraps each of the notes to `width`.
+fn wrap_notes(notes: &[String], width: f64) -> Vec<String> {
+    notes
         .iter()
-        .flat_map(|c| c.barriers.clone())
-        .collect::<HashSet<String>>()
-        .len();
-    calculate_components_container_height_by_count(barriers as f64)
+        .flat_map(|note| wrap_text(note, width, NOTE_FONT_SIZE))
+        .collect()
+}
+
+/// Wraps the name of a barrier to fit the label list, leaving room on the
+/// first line for its id. Names over `max_label_chars` are truncated first.
+fn wrap_barrier_label(
+    barrier: &str,
+    width: f64,
+    font_size: f64,
+    max_label_chars: Option<usize>,
+) -> Vec<String> {
+    let width = width - text_width(BARRIER_ID_ALLOWANCE, font_size);
+    let label = truncate_label(barrier, max_label_chars);
+    wrap_text(label.as_deref().unwrap_or(barrier), width, font_size)
+}
+
+/// Returns the reference number of the `i`th component of a kind, e.g. C1,
+/// if components are numbered.
+fn get_component_number(kind: &ComponentKind, i: usize, options: &Options) -> Option<String> {
+    if !options.number_components {
+        return None;
+    }
+    let prefix = match kind {
+        ComponentKind::Cause => &options.component_number_prefixes.cause,
+        ComponentKind::Consequence => &options.component_number_prefixes.consequence,
+    };
+    Some(format!("{prefix}{}", i + 1))
+}
+
+/// Cuts text longer than `max_chars` characters short, ending it with an
+/// ellipsis. Returns `None` if the text fits.
+fn truncate_label(text: &str, max_chars: Option<usize>) -> Option<String> {
+    let max_chars = max_chars?;
+    if text.chars().count() <= max_chars {
+        return None;
+    }
+    let mut truncated = text
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect::<String>();
+    truncated.push('…');
+    Some(truncated)
+}
+
+/// Breaks text into lines that fit the width, between words where
+/// possible. Words longer than a line are broken across lines.
+fn wrap_text(text: &str, width: f64, font_size: f64) -> Vec<String> {
+    let max_chars = ((width / (font_size * CHARACTER_WIDTH)).floor() as usize).max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut chars = word.chars().collect::<Vec<_>>();
+        while chars.len() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(chars.drain(..max_chars).collect());
+        }
+        if chars.is_empty() {
+            continue;
+        }
+        let line_length = line.chars().count();
+        if line_length > 0 && line_length + 1 + chars.len() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(chars);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Wraps text to the width of `rectangle` as the diagram's labels are
+/// wrapped, shrinking the font from `base_font_size` step by step until the
+/// lines fit its height too. Returns the lines and the font size they fit
+/// at, which stops shrinking at a quarter of the base size. Renderers can
+/// use it to place text consistently with the crate's layout.
+pub fn fit_text(text: &str, rectangle: &Rectangle, base_font_size: f64) -> (Vec<String>, f64) {
+    let mut scale = 1.0;
+    loop {
+        let font_size = base_font_size * scale;
+        let lines = wrap_text(text, rectangle.width, font_size);
+        let height = lines.len() as f64 * font_size * LINE_SPACING;
+        let fits = height <= rectangle.height
+            && lines
+                .iter()
+                .all(|line| text_width(line, font_size) <= rectangle.width);
+        if fits || scale * FONT_SHRINK_FACTOR < MIN_FONT_SCALE {
+            return (lines, font_size);
+        }
+        scale *= FONT_SHRINK_FACTOR;
+    }
+}
+
+/// Returns the width taken by the barrier columns of a side, or by a single
+/// column when they are stacked into a panel.
+fn calculate_barriers_container_width(barriers: &[&str], options: &Options) -> f64 {
+    let (barrier_width, _) = get_barrier_box_size(options);
+    if exceeds_stack_threshold(barriers, options) {
+        calculate_barriers_container_width_by_count(1.0, barrier_width)
+    } else {
+        calculate_barriers_container_width_by_count(barriers.len() as f64, barrier_width)
+    }
 }
 
-fn calculate_barriers_container_width(barriers: &HashSet<&str>) -> f64 {
-    let barriers_count = barriers.len() as f64;
+fn calculate_barriers_container_width_by_count(barriers_count: f64, barrier_width: f64) -> f64 {
     let padding = BARRIERS_CONTAINER_HORIZONTAL_PADDING * 2.0;
-    barriers_count * BARRIER_WIDTH + ((barriers_count - 1.0) * BARRIER_MARGIN_RIGHT) + padding
+    barriers_count * barrier_width + ((barriers_count - 1.0) * BARRIER_MARGIN_RIGHT) + padding
+}
+
+/// Returns the width and height of the barrier boxes.
+fn get_barrier_box_size(options: &Options) -> (f64, f64) {
+    options
+        .barrier_box_size
+        .unwrap_or((BARRIER_WIDTH, COMPONENT_HEIGHT))
 }
 
 fn calculate_canvas_width(
-    diagram: &Diagram,
+    event_radius: f64,
     max_component_box_width: f64,
     max_barriers_container_width: f64,
 ) -> f64 {
-    calculate_event_circle_radius(&diagram.event)
-        + (max_component_box_width * 2.0)
-        + (max_barriers_container_width * 2.0)
+    event_radius + (max_component_box_width * 2.0) + (max_barriers_container_width * 2.0)
+}
+
+/// Whether the barriers of a side are listed in a panel instead of columns.
+fn is_stacked(components: &[&Component], options: &Options) -> bool {
+    exceeds_stack_threshold(&filter_barriers(components), options)
+}
+
+/// Returns whether the barrier names of a side are written along their
+/// columns rather than listed.
+fn has_column_labels(components: &[&Component], options: &Options) -> bool {
+    options.barrier_label_placement == BarrierLabelPlacement::Column
+        && !is_stacked(components, options)
 }
 
-fn calculate_max_barriers_container_width(a: &HashSet<&str>, b: &HashSet<&str>) -> f64 {
-    let aw = calculate_barriers_container_width(a);
-    let bw = calculate_barriers_container_width(b);
+fn exceeds_stack_threshold(barriers: &[&str], options: &Options) -> bool {
+    let Some(threshold) = options.barrier_stack_threshold else {
+        return false;
+    };
+    let (barrier_width, _) = get_barrier_box_size(options);
+    calculate_barriers_container_width_by_count(barriers.len() as f64, barrier_width) > threshold
+}
+
+fn calculate_max_barriers_container_width(a: &[&str], b: &[&str], options: &Options) -> f64 {
+    let aw = calculate_barriers_container_width(a, options);
+    let bw = calculate_barriers_container_width(b, options);
     aw.max(bw)
 }
 
-fn calculate_max_components_box_width(a: &[&Component], b: &[&Component]) -> f64 {
-    let aw = calculate_max_component_box_width(a);
-    let bw = calculate_max_component_box_width(b);
+fn calculate_max_components_box_width(
+    a: &[&Component],
+    b: &[&Component],
+    font_size: f64,
+    options: &Options,
+) -> f64 {
+    let aw = calculate_max_component_box_width(a, font_size, options);
+    let bw = calculate_max_component_box_width(b, font_size, options);
     aw.max(bw)
 }
 
-fn calculate_max_component_box_width(components: &[&Component]) -> f64 {
+fn calculate_max_component_box_width(
+    components: &[&Component],
+    font_size: f64,
+    options: &Options,
+) -> f64 {
     components
         .iter()
-        .map(|c| text_width(&c.name) as u32)
+        .enumerate()
+        .map(|(i, c)| {
+            let icon_width = if c.icon.is_some() {
+                ICON_ALLOWANCE
+            } else {
+                0.0
+            };
+            let number_width = get_component_number(&c.kind, i, options).map_or(0.0, |n| {
+                text_width(&n, font_size * COMPONENT_NUMBER_FONT_SCALE) + COMPONENT_NUMBER_MARGIN
+            });
+            let label = truncate_label(&c.name, options.max_label_chars);
+            let label = label.as_deref().unwrap_or(&c.name);
+            let description_width = c.description.as_deref().map_or(0.0, |d| {
+                let width = text_width(d, font_size * DESCRIPTION_FONT_SCALE);
+                width.min(DESCRIPTION_MAX_WIDTH) + DESCRIPTION_PADDING * 2.0
+            });
+            let header_width = text_width(label, font_size) + icon_width + number_width;
+            header_width.max(description_width) as u32
+        })
         .max()
         .map(|v| v as f64)
         .unwrap_or(0.0)
 }
 
+/// Causes are drawn on the left and consequences on the right, unless the
+/// diagram is mirrored.
+fn get_placement(kind: &ComponentKind, mirror: bool) -> Placement {
+    match (kind, mirror) {
+        (ComponentKind::Cause, false) | (ComponentKind::Consequence, true) => Placement::Left,
+        (ComponentKind::Cause, true) | (ComponentKind::Consequence, false) => Placement::Right,
+    }
+}
+
 fn get_component_x_center(kind: &ComponentKind, ctx: &Context) -> f64 {
-    match kind {
-        ComponentKind::Cause => (ctx.max_component_box_width / 2.0) + COMPONENT_PADDING_X,
-        ComponentKind::Consequence => {
-            ctx.canvas_width - (ctx.max_component_box_width / 2.0) - COMPONENT_PADDING_X
+    let content_left = (ctx.canvas_width - ctx.content_width) / 2.0;
+    let margin = ctx.category_band_width + ctx.spacing.component_padding;
+    match get_placement(kind, ctx.mirror) {
+        Placement::Left => content_left + (ctx.max_component_box_width / 2.0) + margin,
+        Placement::Right => {
+            content_left + ctx.content_width - (ctx.max_component_box_width / 2.0) - margin
         }
     }
 }
 
+/// Returns the vertical centre of the `i`th component of a kind. Negative
+/// indices are the rows above the components, holding the barrier ids and
+/// headers, which keep the height of a header row.
 fn get_component_y_center(i: f64, kind: &ComponentKind, ctx: &Context) -> f64 {
     let container_height = match kind {
         ComponentKind::Cause => ctx.causes_container_height,
         ComponentKind::Consequence => ctx.consequences_container_height,
     };
-    let components_container_top = (ctx.canvas_height / 2.0) - (container_height / 2.0);
-    let y_relative = i * COMPONENT_HEIGHT + (i * COMPONENT_MARGIN_BOTTOM);
-    components_container_top + y_relative + (COMPONENT_HEIGHT / 2.0)
+    let tallest_container_height = ctx
+        .causes_container_height
+        .max(ctx.consequences_container_height);
+    let components_container_top = match ctx.component_alignment {
+        ComponentAlignment::Center => ctx.event_centre.y - (container_height / 2.0),
+        ComponentAlignment::Top => ctx.event_centre.y - (tallest_container_height / 2.0),
+        ComponentAlignment::Bottom => {
+            ctx.event_centre.y + (tallest_container_height / 2.0) - container_height
+        }
+    };
+    let height = if i < 0.0 {
+        COMPONENT_HEIGHT
+    } else {
+        ctx.component_height
+    };
+    let y_relative = i * height + (i * ctx.spacing.component_margin);
+    components_container_top + y_relative + (height / 2.0)
 }
 
 fn get_barrier_x_center(i: f64, kind: &ComponentKind, ctx: &Context) -> f64 {
     let component_x = get_component_x_center(kind, ctx);
-    match kind {
-        ComponentKind::Cause => {
+    match get_placement(kind, ctx.mirror) {
+        Placement::Left => {
             component_x
                 + (ctx.max_component_box_width / 2.0)
-                + (i * (BARRIER_WIDTH + BARRIER_PADDING_RIGHT))
+                + (i * (ctx.barrier_width + BARRIER_PADDING_RIGHT))
                 + ((i + 1.0) * BARRIER_PADDING_RIGHT)
-                + BARRIER_WIDTH / 2.0
+                + ctx.barrier_width / 2.0
         }
-        ComponentKind::Consequence => {
+        Placement::Right => {
             component_x
                 - (ctx.max_component_box_width / 2.0)
-                - (i * (BARRIER_WIDTH))
+                - (i * (ctx.barrier_width + BARRIER_PADDING_RIGHT))
                 - ((i + 1.0) * BARRIER_PADDING_RIGHT)
-                - BARRIER_WIDTH / 2.0
+                - ctx.barrier_width / 2.0
+        }
+    }
+}
+
+/// Returns the centre of the stacked barriers panel, halfway between the
+/// components and the event circle.
+fn get_stacked_barriers_x_center(kind: &ComponentKind, ctx: &Context) -> f64 {
+    let component_x = get_component_x_center(kind, ctx);
+    let half_box_width = ctx.max_component_box_width / 2.0;
+    match get_placement(kind, ctx.mirror) {
+        Placement::Left => {
+            (component_x + half_box_width + ctx.event_centre.x - ctx.event_radius) / 2.0
+        }
+        Placement::Right => {
+            (component_x - half_box_width + ctx.event_centre.x + ctx.event_radius) / 2.0
         }
     }
 }
 
-pub fn text_width(text: &str) -> f64 {
-    text.len() as f64 * 15.0
+/// Returns the centre of the `i`th central barrier slot, counted outwards
+/// from the event circle.
+fn get_central_barrier_x_center(i: f64, kind: &ComponentKind, ctx: &Context) -> f64 {
+    let offset = ctx.event_radius
+        + BARRIER_PADDING_RIGHT
+        + ctx.barrier_width / 2.0
+        + i * (ctx.barrier_width + BARRIER_PADDING_RIGHT);
+    match get_placement(kind, ctx.mirror) {
+        Placement::Left => ctx.event_centre.x - offset,
+        Placement::Right => ctx.event_centre.x + offset,
+    }
+}
+
+pub fn text_width(text: &str, font_size: f64) -> f64 {
+    text.len() as f64 * font_size * CHARACTER_WIDTH
 }
 
 /// Adjusts the y-axis, given the x-axis, of a point on
@@ -414,16 +2457,145 @@ fn get_slope_point(from: &Vector2, to: &Vector2, x: f64) -> Vector2 {
     Vector2 { x, y }
 }
 
-fn get_barrier_label_alignment(kind: &ComponentKind) -> Alignment {
-    match kind {
-        ComponentKind::Cause => Alignment::Left,
-        ComponentKind::Consequence => Alignment::Right,
+fn get_score_color(score: f64, thresholds: &ScoreThresholds) -> Color {
+    if score < thresholds.amber {
+        LOW_RISK_COLOR
+    } else if score <= thresholds.red {
+        MEDIUM_RISK_COLOR
+    } else {
+        HIGH_RISK_COLOR
+    }
+}
+
+#[cfg(feature = "timestamp")]
+fn get_timestamp(options: &Options) -> Option<String> {
+    if !options.timestamp_watermark {
+        return None;
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC");
+    Some(format!("Generated {now}"))
+}
+
+#[cfg(not(feature = "timestamp"))]
+fn get_timestamp(_options: &Options) -> Option<String> {
+    None
+}
+
+/// Maps a severity from 1 to 5 onto the risk colours.
+fn get_severity_color(severity: u8) -> Color {
+    match severity {
+        1 | 2 => LOW_RISK_COLOR,
+        3 => MEDIUM_RISK_COLOR,
+        _ => HIGH_RISK_COLOR,
+    }
+}
+
+/// Returns the style of a barrier's boxes. Shared barriers are outlined in
+/// a highlight colour, or filled with a tint of it when failed, as failed
+/// barriers keep their red outline. Absent barriers are drawn as hollow,
+/// dashed boxes whatever their state.
+fn get_barrier_style(state: BarrierState, shared: bool, absent: bool) -> Style {
+    if absent {
+        return Style {
+            fill: Some(Color::WHITE),
+            stroke: Some(ABSENT_BARRIER_COLOR),
+            dashed: true,
+            ..Style::with_class("barrier barrier-absent")
+        };
+    }
+    match (state, shared) {
+        (BarrierState::Effective, false) => Style::with_class("barrier"),
+        (BarrierState::Effective, true) => Style {
+            stroke: Some(SHARED_BARRIER_COLOR),
+            ..Style::with_class("barrier barrier-shared")
+        },
+        (BarrierState::Degraded, false) => Style {
+            fill: Some(DEGRADED_BARRIER_COLOR),
+            ..Style::with_class("barrier barrier-degraded")
+        },
+        (BarrierState::Degraded, true) => Style {
+            fill: Some(DEGRADED_BARRIER_COLOR),
+            stroke: Some(SHARED_BARRIER_COLOR),
+            ..Style::with_class("barrier barrier-degraded barrier-shared")
+        },
+        (BarrierState::Failed, false) => Style {
+            stroke: Some(FAILED_BARRIER_COLOR),
+            ..Style::with_class("barrier barrier-failed")
+        },
+        (BarrierState::Failed, true) => Style {
+            fill: Some(SHARED_BARRIER_TINT),
+            stroke: Some(FAILED_BARRIER_COLOR),
+            ..Style::with_class("barrier barrier-failed barrier-shared")
+        },
+    }
+}
+
+/// Outlines an element added since the previous version in green, and
+/// draws one removed since then dashed and outlined in red. The element's
+/// class is replaced by the matching one of `classes`.
+fn apply_change(style: Style, change: Option<Change>, classes: [&'static str; 2]) -> Style {
+    match change {
+        None => style,
+        Some(Change::Added) => Style {
+            stroke: Some(ADDED_COLOR),
+            class: Some(classes[0]),
+            ..style
+        },
+        Some(Change::Removed) => Style {
+            stroke: Some(REMOVED_COLOR),
+            dashed: true,
+            class: Some(classes[1]),
+            ..style
+        },
+    }
+}
+
+/// Draws an X across the rectangle, marking it as failed.
+fn render_cross<R>(r: R, rectangle: &Rectangle) -> R
+where
+    R: Renderer,
+{
+    let half_width = rectangle.width / 2.0;
+    let half_height = rectangle.height / 2.0;
+    let centre = rectangle.centre;
+    let style = Style {
+        stroke: Some(FAILED_BARRIER_COLOR),
+        ..Style::with_class("barrier-failed")
+    };
+    r.draw_line(
+        &Vector2 {
+            x: centre.x - half_width,
+            y: centre.y - half_height,
+        },
+        &Vector2 {
+            x: centre.x + half_width,
+            y: centre.y + half_height,
+        },
+        &style,
+    )
+    .draw_line(
+        &Vector2 {
+            x: centre.x + half_width,
+            y: centre.y - half_height,
+        },
+        &Vector2 {
+            x: centre.x - half_width,
+            y: centre.y + half_height,
+        },
+        &style,
+    )
+}
+
+fn get_barrier_label_alignment(placement: Placement) -> Alignment {
+    match placement {
+        Placement::Left => Alignment::Left,
+        Placement::Right => Alignment::Right,
     }
 }
 
-fn get_barrier_label(kind: &ComponentKind, label_id: &str, barrier: &str) -> String {
-    match kind {
-        ComponentKind::Cause => format!("[{label_id}] {barrier}"),
-        ComponentKind::Consequence => format!("{barrier} [{label_id}]"),
+fn get_barrier_label(placement: Placement, label_id: &str, barrier: &str) -> String {
+    match placement {
+        Placement::Left => format!("[{label_id}] {barrier}"),
+        Placement::Right => format!("{barrier} [{label_id}]"),
     }
 }
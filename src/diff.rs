@@ -0,0 +1,151 @@
+use crate::brush::Brush;
+use crate::renderer::Renderer;
+use crate::{Component, ComponentKind, Diagram, Options};
+
+/// Components and barriers added to or removed from a diagram since a
+/// previous version of it, by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiagramDiff {
+    pub added_causes: Vec<String>,
+    pub removed_causes: Vec<String>,
+    pub added_consequences: Vec<String>,
+    pub removed_consequences: Vec<String>,
+    pub added_barriers: Vec<String>,
+    pub removed_barriers: Vec<String>,
+}
+
+/// Change of an element relative to the previous version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Change {
+    Added,
+    Removed,
+}
+
+impl Diagram {
+    /// Compares this diagram with a previous version of it.
+    pub fn diff(&self, previous: &Diagram) -> DiagramDiff {
+        let names = |diagram: &Diagram, kind: ComponentKind| {
+            diagram
+                .components
+                .iter()
+                .filter(|c| c.kind == kind)
+                .map(|c| c.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let barrier_names = |diagram: &Diagram| {
+            diagram
+                .barriers
+                .iter()
+                .map(|b| b.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let (added_causes, removed_causes) = compare(
+            names(self, ComponentKind::Cause),
+            names(previous, ComponentKind::Cause),
+        );
+        let (added_consequences, removed_consequences) = compare(
+            names(self, ComponentKind::Consequence),
+            names(previous, ComponentKind::Consequence),
+        );
+        let (added_barriers, removed_barriers) =
+            compare(barrier_names(self), barrier_names(previous));
+        DiagramDiff {
+            added_causes,
+            removed_causes,
+            added_consequences,
+            removed_consequences,
+            added_barriers,
+            removed_barriers,
+        }
+    }
+
+    /// Renders the diagram with the changes since a previous version
+    /// highlighted: added components and barriers are outlined in green,
+    /// while removed ones are drawn back in, dashed and outlined in red.
+    pub fn render_diff<R>(&self, previous: &Diagram, renderer: R, options: &Options) -> Vec<u8>
+    where
+        R: Renderer,
+    {
+        let diff = self.diff(previous);
+        let combined = self.with_removed(previous, &diff);
+        Brush::new(&combined, options)
+            .with_diff(&diff)
+            .render(renderer)
+            .into_bytes()
+    }
+
+    /// Returns a copy of this diagram with the components and barriers
+    /// removed since the previous version added back.
+    fn with_removed(&self, previous: &Diagram, diff: &DiagramDiff) -> Diagram {
+        let mut combined = self.clone();
+        let removed_barriers = previous
+            .barriers
+            .iter()
+            .filter(|b| diff.removed_barriers.contains(&b.name));
+        combined.barriers.extend(removed_barriers.cloned());
+        for component in &previous.components {
+            let existing = combined
+                .components
+                .iter_mut()
+                .find(|c| c.name == component.name && c.kind == component.kind);
+            let Some(existing) = existing else {
+                combined.components.push(component.clone());
+                continue;
+            };
+            let removed_barriers = component
+                .barriers
+                .iter()
+                .filter(|b| diff.removed_barriers.contains(b));
+            existing.barriers.extend(removed_barriers.cloned());
+        }
+        combined
+    }
+}
+
+impl DiagramDiff {
+    /// Whether the diagrams have the same components and barriers.
+    pub fn is_empty(&self) -> bool {
+        self.added_causes.is_empty()
+            && self.removed_causes.is_empty()
+            && self.added_consequences.is_empty()
+            && self.removed_consequences.is_empty()
+            && self.added_barriers.is_empty()
+            && self.removed_barriers.is_empty()
+    }
+
+    pub(crate) fn component_change(&self, component: &Component) -> Option<Change> {
+        let (added, removed) = match component.kind {
+            ComponentKind::Cause => (&self.added_causes, &self.removed_causes),
+            ComponentKind::Consequence => (&self.added_consequences, &self.removed_consequences),
+        };
+        change(&component.name, added, removed)
+    }
+
+    pub(crate) fn barrier_change(&self, barrier: &str) -> Option<Change> {
+        change(barrier, &self.added_barriers, &self.removed_barriers)
+    }
+}
+
+/// Returns the names only in `current`, and those only in `previous`.
+fn compare(current: Vec<String>, previous: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let added = current
+        .iter()
+        .filter(|name| !previous.contains(name))
+        .cloned()
+        .collect();
+    let removed = previous
+        .into_iter()
+        .filter(|name| !current.contains(name))
+        .collect();
+    (added, removed)
+}
+
+fn change(name: &str, added: &[String], removed: &[String]) -> Option<Change> {
+    if added.iter().any(|n| n == name) {
+        Some(Change::Added)
+    } else if removed.iter().any(|n| n == name) {
+        Some(Change::Removed)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,244 @@
+use crate::renderer::Color;
+use crate::{
+    BarrierLabelPlacement, BarrierOrder, ComponentAlignment, Density, Options, Side, Theme, Warning,
+};
+use std::fmt;
+use std::str::FromStr;
+
+const FENCE: &str = "---";
+
+/// Error returned for a malformed front matter block, with the line of the
+/// input it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatterError {
+    line: usize,
+    message: String,
+}
+
+impl Options {
+    /// Applies the options set in the input's front matter, a leading block
+    /// of `key: value` lines fenced by `---`, on top of these options. Keys
+    /// are the names of option fields, with font sizes written as
+    /// `font_sizes.title` and the canvas size as `800x600`. The
+    /// [`Options::renderer_settings`] are written without their prefix, as
+    /// `font_family` and `stroke_width`, and `theme` and `density` apply
+    /// the presets of [`Theme`] and [`crate::Density`], overridden by the
+    /// keys following them. Lines starting with `#` are comments.
+    ///
+    /// Input without front matter leaves the options unchanged.
+    pub fn with_front_matter(&self, input: &str) -> Result<Options, FrontMatterError> {
+        let mut options = self.clone();
+        let Some((block, _)) = split_front_matter(input)? else {
+            return Ok(options);
+        };
+        // The block starts after the opening fence, on the second line.
+        for (i, line) in block.lines().enumerate() {
+            let line_number = i + 2;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| FrontMatterError {
+                line: line_number,
+                message,
+            };
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(error(format!("expected `key: value`, found `{line}`")));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            set_option(&mut options, key, value)
+                .map_err(|message| error(format!("{key}: {message}")))?;
+        }
+        Ok(options)
+    }
+}
+
+impl FrontMatterError {
+    /// Line of the input the error was found on, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub(crate) fn into_warning(self) -> Warning {
+        let message = format!("front matter ignored: {}", self.message);
+        Warning::new(Some(self.line), message)
+    }
+}
+
+impl fmt::Display for FrontMatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "front matter, line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for FrontMatterError {}
+
+/// Returns the input without its front matter, if it has any. Front matter
+/// missing its closing fence runs to the end of the input, so that none of
+/// its lines are parsed as commands.
+pub(crate) fn strip_front_matter(input: &str) -> &str {
+    match split_front_matter(input) {
+        Ok(Some((_, body))) => body,
+        Ok(None) => input,
+        Err(_) => &input[input.len()..],
+    }
+}
+
+/// Splits the input into the lines between the fences of its front matter
+/// and the body following the closing fence.
+fn split_front_matter(input: &str) -> Result<Option<(&str, &str)>, FrontMatterError> {
+    let Some(rest) = input.strip_prefix(FENCE) else {
+        return Ok(None);
+    };
+    let Some((opening, block)) = rest.split_once('\n') else {
+        return Ok(None);
+    };
+    // Anything after the fence, e.g. `----`, isn't front matter.
+    if !opening.trim().is_empty() {
+        return Ok(None);
+    }
+    let mut offset = 0;
+    for line in block.split_inclusive('\n') {
+        if line.trim_end() == FENCE {
+            return Ok(Some((&block[..offset], &block[offset + line.len()..])));
+        }
+        offset += line.len();
+    }
+    Err(FrontMatterError {
+        line: 1,
+        message: "missing closing `---`".to_owned(),
+    })
+}
+
+fn set_option(options: &mut Options, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "min_canvas_width" => options.min_canvas_width = parse(value)?,
+        "min_canvas_height" => options.min_canvas_height = parse(value)?,
+        "canvas_size" => {
+            let (width, height) = value
+                .split_once('x')
+                .ok_or_else(|| format!("expected `WIDTHxHEIGHT`, found `{value}`"))?;
+            options.canvas_size = Some((parse(width.trim())?, parse(height.trim())?));
+        }
+        "max_canvas_width" => options.max_canvas_width = Some(parse(value)?),
+        "canvas_size_limit" => {
+            let (width, height) = value
+                .split_once('x')
+                .ok_or_else(|| format!("expected `WIDTHxHEIGHT`, found `{value}`"))?;
+            options.canvas_size_limit = Some((parse(width.trim())?, parse(height.trim())?));
+        }
+        "min_event_radius" => options.min_event_radius = parse(value)?,
+        "event_caption" => options.event_caption = parse(value)?,
+        "event_placeholder" => options.event_placeholder = Some(value.to_owned()),
+        "side" => {
+            options.side = Some(match value {
+                "causes" => Side::Causes,
+                "consequences" => Side::Consequences,
+                _ => {
+                    return Err(format!(
+                        "expected `causes` or `consequences`, found `{value}`"
+                    ))
+                }
+            })
+        }
+        "connector_spread" => options.connector_spread = parse(value)?,
+        "barrier_order" => {
+            options.barrier_order = match value {
+                "frequency" => BarrierOrder::Frequency,
+                "effectiveness" => BarrierOrder::Effectiveness,
+                _ => {
+                    return Err(format!(
+                        "expected `frequency` or `effectiveness`, found `{value}`"
+                    ))
+                }
+            }
+        }
+        "component_alignment" => {
+            options.component_alignment = match value {
+                "center" => ComponentAlignment::Center,
+                "top" => ComponentAlignment::Top,
+                "bottom" => ComponentAlignment::Bottom,
+                _ => {
+                    return Err(format!(
+                        "expected `center`, `top` or `bottom`, found `{value}`"
+                    ))
+                }
+            }
+        }
+        "barrier_label_placement" => {
+            options.barrier_label_placement = match value {
+                "list" => BarrierLabelPlacement::List,
+                "column" => BarrierLabelPlacement::Column,
+                _ => return Err(format!("expected `list` or `column`, found `{value}`")),
+            }
+        }
+        "barrier_box_size" => {
+            let (width, height) = value
+                .split_once('x')
+                .ok_or_else(|| format!("expected `WIDTHxHEIGHT`, found `{value}`"))?;
+            options.barrier_box_size = Some((parse(width.trim())?, parse(height.trim())?));
+        }
+        "merge_barrier_boxes" => options.merge_barrier_boxes = parse(value)?,
+        "color_connectors" => options.color_connectors = parse(value)?,
+        "likelihood_connector_widths" => {
+            let (min, max) = value
+                .split_once("..")
+                .ok_or_else(|| format!("expected `MIN..MAX`, found `{value}`"))?;
+            options.likelihood_connector_widths = Some((parse(min.trim())?, parse(max.trim())?));
+        }
+        "highlight_shared_barriers" => options.highlight_shared_barriers = parse(value)?,
+        "barrier_stack_threshold" => options.barrier_stack_threshold = Some(parse(value)?),
+        "debug_border" => options.debug_border = parse(value)?,
+        "grid_spacing" => options.grid_spacing = Some(parse(value)?),
+        "spine" => options.spine = parse(value)?,
+        "spine_color" => {
+            options.spine_color = Some(Color::parse(value).map_err(|e| e.to_string())?)
+        }
+        "watermark" => options.watermark = Some(value.to_owned()),
+        #[cfg(feature = "timestamp")]
+        "timestamp_watermark" => options.timestamp_watermark = parse(value)?,
+        "max_label_chars" => options.max_label_chars = Some(parse(value)?),
+        "number_components" => options.number_components = parse(value)?,
+        "show_residual_likelihood" => options.show_residual_likelihood = parse(value)?,
+        "count_badges" => options.count_badges = parse(value)?,
+        "mirror" => options.mirror = parse(value)?,
+        "font_sizes.title" => options.font_sizes.title = parse(value)?,
+        "font_sizes.event" => options.font_sizes.event = parse(value)?,
+        "font_sizes.component" => options.font_sizes.component = parse(value)?,
+        "font_sizes.barrier" => options.font_sizes.barrier = parse(value)?,
+        "font_sizes.pathway" => options.font_sizes.pathway = parse(value)?,
+        "density" => {
+            let density = match value {
+                "compact" => Density::Compact,
+                "normal" => Density::Normal,
+                "spacious" => Density::Spacious,
+                _ => {
+                    return Err(format!(
+                        "expected `compact`, `normal` or `spacious`, found `{value}`"
+                    ))
+                }
+            };
+            options.spacing = density.spacing();
+        }
+        "theme" => {
+            let theme = match value {
+                "plain" => Theme::Plain,
+                "colorful" => Theme::Colorful,
+                _ => return Err(format!("expected `plain` or `colorful`, found `{value}`")),
+            };
+            theme.apply(options);
+        }
+        "font_family" => options.renderer_settings.font_family = Some(value.to_owned()),
+        "stroke_width" => options.renderer_settings.stroke_width = Some(parse(value)?),
+        "spacing.component_margin" => options.spacing.component_margin = parse(value)?,
+        "spacing.component_padding" => options.spacing.component_padding = parse(value)?,
+        _ => return Err("unknown option".to_owned()),
+    }
+    Ok(())
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}`"))
+}
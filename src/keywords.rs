@@ -0,0 +1,123 @@
+/// Command keywords of the DSL, so diagrams can be written in languages
+/// other than English.
+#[derive(Clone, Debug)]
+pub struct Keywords {
+    pub title: String,
+    pub cause: String,
+    pub consequence: String,
+    pub event: String,
+    /// Sets the radius of the event circle, whatever the event's text.
+    pub event_radius: String,
+    pub rating: String,
+    pub defaults: String,
+    pub barrier: String,
+    /// Declares a barrier protecting causes only.
+    pub preventive: String,
+    /// Declares a barrier protecting consequences only.
+    pub recovery: String,
+    pub pathway: String,
+    /// Gives a component a short description, written below its name.
+    pub describe: String,
+    pub category: String,
+    pub icon: String,
+    pub gate: String,
+    pub route: String,
+    pub note: String,
+}
+
+/// Command of a line, independent of its keyword.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Command {
+    Title,
+    Cause,
+    Consequence,
+    Event,
+    EventRadius,
+    Rating,
+    Defaults,
+    Barrier,
+    Preventive,
+    Recovery,
+    Pathway,
+    Describe,
+    Category,
+    Icon,
+    Gate,
+    Route,
+    Note,
+}
+
+impl Keywords {
+    /// Portuguese keywords, without diacritics.
+    pub fn portuguese() -> Self {
+        Keywords {
+            title: "titulo".to_owned(),
+            cause: "causa".to_owned(),
+            consequence: "consequencia".to_owned(),
+            event: "evento".to_owned(),
+            event_radius: "raio_evento".to_owned(),
+            rating: "classificacao".to_owned(),
+            defaults: "padroes".to_owned(),
+            barrier: "barreira".to_owned(),
+            preventive: "preventiva".to_owned(),
+            recovery: "recuperacao".to_owned(),
+            pathway: "caminho".to_owned(),
+            describe: "descricao".to_owned(),
+            category: "categoria".to_owned(),
+            icon: "icone".to_owned(),
+            gate: "porta".to_owned(),
+            route: "rota".to_owned(),
+            note: "nota".to_owned(),
+        }
+    }
+
+    pub(crate) fn command(&self, keyword: &str) -> Option<Command> {
+        let commands = [
+            (&self.title, Command::Title),
+            (&self.cause, Command::Cause),
+            (&self.consequence, Command::Consequence),
+            (&self.event, Command::Event),
+            (&self.event_radius, Command::EventRadius),
+            (&self.rating, Command::Rating),
+            (&self.defaults, Command::Defaults),
+            (&self.barrier, Command::Barrier),
+            (&self.preventive, Command::Preventive),
+            (&self.recovery, Command::Recovery),
+            (&self.pathway, Command::Pathway),
+            (&self.describe, Command::Describe),
+            (&self.category, Command::Category),
+            (&self.icon, Command::Icon),
+            (&self.gate, Command::Gate),
+            (&self.route, Command::Route),
+            (&self.note, Command::Note),
+        ];
+        commands
+            .into_iter()
+            .find(|(k, _)| k.as_str() == keyword)
+            .map(|(_, command)| command)
+    }
+}
+
+impl Default for Keywords {
+    fn default() -> Self {
+        Keywords {
+            title: "title".to_owned(),
+            cause: "cause".to_owned(),
+            consequence: "consequence".to_owned(),
+            event: "event".to_owned(),
+            event_radius: "event_radius".to_owned(),
+            rating: "rating".to_owned(),
+            defaults: "defaults".to_owned(),
+            barrier: "barrier".to_owned(),
+            preventive: "preventive".to_owned(),
+            recovery: "recovery".to_owned(),
+            pathway: "pathway".to_owned(),
+            describe: "describe".to_owned(),
+            category: "category".to_owned(),
+            icon: "icon".to_owned(),
+            gate: "gate".to_owned(),
+            route: "route".to_owned(),
+            note: "note".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::brush::Brush;
+use crate::renderer::Renderer;
+use crate::{Diagram, Options};
+
+/// Geometry of a diagram, computed once so that it can be rendered any number
+/// of times, e.g. by an editor re-rendering on every change that doesn't
+/// alter the diagram's structure.
+pub struct Layout<'d> {
+    brush: Brush<'d>,
+}
+
+impl Diagram {
+    /// Computes the layout of the diagram without rendering it.
+    pub fn layout(&self, options: &Options) -> Layout<'_> {
+        Layout {
+            brush: Brush::new(self, options),
+        }
+    }
+}
+
+impl Layout<'_> {
+    pub fn canvas_width(&self) -> f64 {
+        self.brush.canvas_width()
+    }
+
+    pub fn canvas_height(&self) -> f64 {
+        self.brush.canvas_height()
+    }
+
+    pub fn render<R>(&self, renderer: R) -> Vec<u8>
+    where
+        R: Renderer,
+    {
+        self.brush.render(renderer).into_bytes()
+    }
+}
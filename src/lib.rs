@@ -1,99 +1,1354 @@
-use crate::brush::Brush;
-use crate::renderer::Renderer;
+use crate::brush::{filter_barriers, filter_components, Brush};
+use crate::keywords::Command;
+use crate::renderer::{BoundsRenderer, Color, Icon, Rectangle, Renderer, TileRenderer, Vector2};
+pub use diff::DiagramDiff;
+pub use front_matter::FrontMatterError;
+pub use keywords::Keywords;
+pub use layout::Layout;
+pub use limits::{LimitError, ParseLimits};
+pub use merge::{MergePolicy, MergeReport};
+pub use options::{
+    BarrierLabelPlacement, BarrierOrder, ComponentAlignment, ComponentNumberPrefixes, Density,
+    FontSizes, Options, ScoreThresholds, Side, Spacing, Theme,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+pub use warning::Warning;
+#[cfg(feature = "xlsx")]
+pub use xlsx::XlsxImportError;
 pub(crate) mod brush;
+mod diff;
+mod front_matter;
+mod keywords;
+mod layout;
+mod limits;
+mod merge;
+mod options;
 pub mod renderer;
+mod serialize;
+mod warning;
+#[cfg(feature = "xlsx")]
+mod xlsx;
 
-#[derive(Default, Debug)]
-struct Diagram {
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Diagram {
     title: String,
     event: String,
+    /// Severity of the top event, from 1 to 5.
+    event_severity: Option<u8>,
+    /// Radius of the event circle, in place of the one fitted to the event.
+    event_radius: Option<f64>,
+    rating: Option<Rating>,
     components: Vec<Component>,
+    barriers: Vec<Barrier>,
+    /// Free text written below the diagram, e.g. assumptions and scope,
+    /// one entry per `note` line.
+    notes: Vec<String>,
+    /// Values set by custom commands, see [`parse_diagram_with_handlers`].
+    metadata: BTreeMap<String, String>,
 }
 
-#[derive(Debug)]
-struct Component {
+/// Handles a custom command, given the diagram parsed so far and the
+/// command's value.
+pub type CommandHandler<'h> = Box<dyn FnMut(&mut Diagram, &str) + 'h>;
+
+/// Likelihood and severity of the top event, each in the range 1 to 5.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rating {
+    likelihood: u8,
+    severity: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
     name: String,
     barriers: Vec<String>,
+    /// Barriers of `barriers` the component is only associated with,
+    /// attached with a leading `+`: it shares their column, but no box is
+    /// drawn on its connector.
+    associated_barriers: Vec<String>,
     kind: ComponentKind,
+    /// Risk score from 0 to 100.
+    score: Option<f64>,
+    /// Probability, from 0 to 1, that the cause occurs before any barrier
+    /// acts on it.
+    likelihood: Option<f64>,
+    /// Label of the pathway between the component and the event.
+    pathway: Option<String>,
+    /// Short description written in the body of the component's box,
+    /// below its name.
+    description: Option<String>,
+    /// Category the component is grouped under, e.g. "Equipment".
+    category: Option<String>,
+    /// Symbol drawn in the corner of the component's box.
+    icon: Option<Icon>,
+    /// Logical relationships among the barriers of the component.
+    gates: Vec<Gate>,
+    /// Points, in canvas coordinates, the connector passes through on its
+    /// way from the component to the event.
+    route: Vec<Vector2>,
+}
+
+/// Barriers of a component that only block its pathway together (AND), or
+/// that each block it on their own (OR).
+#[derive(Debug, Clone, PartialEq)]
+struct Gate {
+    operator: GateOperator,
+    barriers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum GateOperator {
+    And,
+    Or,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum ComponentKind {
+#[derive(Debug, Clone, PartialEq)]
+struct Barrier {
+    name: String,
+    group: Option<String>,
+    state: Option<BarrierState>,
+    /// Probability, from 0 to 1, that the barrier stops its pathways.
+    effectiveness: Option<f64>,
+    /// Whether the barrier is the last line of defense, drawn right beside
+    /// the event circle.
+    central: bool,
+    /// Situation the barrier only applies in, e.g. "building occupied".
+    condition: Option<String>,
+    /// Whether the barrier is known to be missing, i.e. one that should be
+    /// in place but isn't, declared with a leading `~`.
+    absent: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+enum BarrierState {
+    #[default]
+    Effective,
+    Degraded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ComponentKind {
     Cause,
     Consequence,
 }
 
+/// Error returned when parsing an unknown component kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseComponentKindError(String);
+
+/// Line separating the diagrams of an input holding several.
+const DIAGRAM_SEPARATOR: &str = "===";
+
+/// Settings declared by `defaults` directives, applied to the
+/// declarations that follow them.
+#[derive(Default)]
+struct ParserState {
+    default_effectiveness: Option<f64>,
+}
+
 pub fn generate_bowtie<R>(input: &str, renderer: R) -> Vec<u8>
 where
     R: Renderer,
 {
-    let diagram = parse_diagram(input);
-    Brush::render_diagram_into_bytes(renderer, &diagram)
+    generate_bowtie_with_options(input, renderer, &Options::default())
+}
+
+/// Renders the diagram with the given options, overridden by those in the
+/// input's front matter. Malformed front matter is logged and its options
+/// ignored; use [`generate_bowtie_with_warnings`] to fail on it instead.
+pub fn generate_bowtie_with_options<R>(input: &str, renderer: R, options: &Options) -> Vec<u8>
+where
+    R: Renderer,
+{
+    let mut warnings = Vec::new();
+    let options = options.with_front_matter(input).unwrap_or_else(|error| {
+        warnings.push(error.into_warning());
+        options.clone()
+    });
+    let bytes = render_reporting(input, renderer, &options, &mut warnings);
+    warning::log(&warnings);
+    bytes
+}
+
+/// Renders the diagram like [`generate_bowtie_with_options`], collecting
+/// the problems worked around on the way into `warnings`: lines that can't
+/// be parsed, references to unknown components, duplicate declarations,
+/// labels cut short and a canvas exceeding [`Options::canvas_size_limit`].
+/// Malformed front matter can't be worked around, and fails instead.
+pub fn generate_bowtie_with_warnings<R>(
+    input: &str,
+    renderer: R,
+    options: &Options,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<u8>, FrontMatterError>
+where
+    R: Renderer,
+{
+    let options = options.with_front_matter(input)?;
+    Ok(render_reporting(input, renderer, &options, warnings))
+}
+
+/// Parses and renders the input with options the front matter was already
+/// applied to, collecting warnings.
+fn render_reporting<R>(
+    input: &str,
+    renderer: R,
+    options: &Options,
+    warnings: &mut Vec<Warning>,
+) -> Vec<u8>
+where
+    R: Renderer,
+{
+    let diagram = parse_diagram_reporting(input, &Keywords::default(), warnings);
+    diagram.check_labels(options, warnings);
+    let brush = Brush::new(&diagram, options);
+    brush.check_canvas_size(warnings);
+    brush.render(renderer).into_bytes()
+}
+
+/// Reads the diagram description from a file and renders it.
+///
+/// Errors opening or reading the file are returned as they are, so a missing
+/// file is reported as [`io::ErrorKind::NotFound`], while a file that isn't
+/// valid UTF-8 is reported as [`io::ErrorKind::InvalidData`]. Parsing itself
+/// doesn't fail: lines that can't be parsed are skipped. Malformed front
+/// matter is also reported as [`io::ErrorKind::InvalidData`], with a
+/// [`FrontMatterError`] as its source.
+pub fn generate_bowtie_from_file<P, R>(path: P, renderer: R) -> io::Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+    R: Renderer,
+{
+    let input = std::fs::read_to_string(path)?;
+    let options = Options::default()
+        .with_front_matter(&input)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let diagram = parse_diagram(&input);
+    Ok(Brush::render_diagram_into_bytes(
+        renderer, &diagram, &options,
+    ))
+}
+
+/// Parses and renders many diagrams to SVG in parallel. The output is in
+/// the same order as the inputs, with an error for each input whose front
+/// matter is malformed.
+#[cfg(all(feature = "parallel", feature = "svg"))]
+pub fn generate_many(inputs: &[&str]) -> Vec<Result<Vec<u8>, FrontMatterError>> {
+    use rayon::prelude::*;
+    inputs
+        .par_iter()
+        .map(|input| {
+            let mut warnings = Vec::new();
+            let renderer = renderer::SvgRenderer::new();
+            let bytes =
+                generate_bowtie_with_warnings(input, renderer, &Options::default(), &mut warnings);
+            warning::log(&warnings);
+            bytes
+        })
+        .collect()
+}
+
+/// Summary of a diagram rendered by [`generate_many_with_manifest`], e.g.
+/// for building an index of the outputs.
+#[cfg(all(feature = "parallel", feature = "svg"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramInfo {
+    pub title: String,
+    pub event: String,
+    pub cause_count: usize,
+    pub consequence_count: usize,
+    pub barrier_count: usize,
+    /// Width of the rendered canvas.
+    pub width: f64,
+    /// Height of the rendered canvas.
+    pub height: f64,
+}
+
+/// Renders many diagrams to SVG in parallel like [`generate_many`], along
+/// with a summary of each, in the same order as the inputs.
+#[cfg(all(feature = "parallel", feature = "svg"))]
+pub fn generate_many_with_manifest(
+    inputs: &[&str],
+) -> Vec<Result<(Vec<u8>, DiagramInfo), FrontMatterError>> {
+    use rayon::prelude::*;
+    inputs
+        .par_iter()
+        .map(|input| {
+            let mut warnings = Vec::new();
+            let options = Options::default().with_front_matter(input)?;
+            let diagram = parse_diagram_reporting(input, &Keywords::default(), &mut warnings);
+            diagram.check_labels(&options, &mut warnings);
+            let brush = Brush::new(&diagram, &options);
+            brush.check_canvas_size(&mut warnings);
+            warning::log(&warnings);
+            let info = DiagramInfo {
+                title: diagram.title.clone(),
+                event: diagram.event.clone(),
+                cause_count: diagram.causes().count(),
+                consequence_count: diagram.consequences().count(),
+                barrier_count: diagram.barrier_count(),
+                width: brush.canvas_width(),
+                height: brush.canvas_height(),
+            };
+            let bytes = brush.render(renderer::SvgRenderer::new()).into_bytes();
+            Ok((bytes, info))
+        })
+        .collect()
+}
+
+/// Draws the bowtie with the given renderer and returns the renderer
+/// instead of its bytes, so that its output can be composed further. Fails
+/// on malformed front matter, before anything is drawn.
+pub fn render_bowtie<R>(input: &str, renderer: R, options: &Options) -> Result<R, FrontMatterError>
+where
+    R: Renderer,
+{
+    let mut warnings = Vec::new();
+    let options = options.with_front_matter(input)?;
+    let diagram = parse_diagram_reporting(input, &Keywords::default(), &mut warnings);
+    let brush = Brush::new(&diagram, &options);
+    brush.check_canvas_size(&mut warnings);
+    warning::log(&warnings);
+    Ok(brush.render(renderer))
+}
+
+/// Renders the diagram in tiles of the given size, e.g. sheets of paper to
+/// print and tape together into a poster. The full layout is cut into a
+/// grid of tiles, returned row by row from the top left, each showing its
+/// part of the canvas with crop marks at its corners. The renderer is
+/// cloned for each tile. Sizes that aren't positive give no tiles.
+/// Malformed front matter fails, like in [`render_bowtie`].
+pub fn generate_tiles<R>(
+    input: &str,
+    renderer: R,
+    tile_width: f64,
+    tile_height: f64,
+) -> Result<Vec<Vec<u8>>, FrontMatterError>
+where
+    R: Renderer + Clone,
+{
+    if !(tile_width > 0.0 && tile_height > 0.0) {
+        return Ok(Vec::new());
+    }
+    let mut warnings = Vec::new();
+    let options = Options::default().with_front_matter(input)?;
+    let diagram = parse_diagram_reporting(input, &Keywords::default(), &mut warnings);
+    let brush = Brush::new(&diagram, &options);
+    brush.check_canvas_size(&mut warnings);
+    warning::log(&warnings);
+    let columns = (brush.canvas_width() / tile_width).ceil() as usize;
+    let rows = (brush.canvas_height() / tile_height).ceil() as usize;
+    let mut tiles = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        for column in 0..columns {
+            let tile = Rectangle {
+                centre: Vector2 {
+                    x: (column as f64 + 0.5) * tile_width,
+                    y: (row as f64 + 0.5) * tile_height,
+                },
+                width: tile_width,
+                height: tile_height,
+            };
+            let renderer = TileRenderer::new(renderer.clone(), tile);
+            tiles.push(brush.render(renderer).into_inner().into_bytes());
+        }
+    }
+    Ok(tiles)
 }
 
 fn parse_diagram(input: &str) -> Diagram {
+    parse_diagram_with_keywords(input, &Keywords::default())
+}
+
+/// Parses every diagram of an input holding several, separated by lines
+/// reading `===`. Each diagram is parsed on its own, with its own title,
+/// event and front matter. Empty sections are skipped.
+pub fn parse_diagrams(input: &str) -> Vec<Diagram> {
+    split_diagrams(input)
+        .into_iter()
+        .filter(|section| !section.trim().is_empty())
+        .map(parse_diagram)
+        .collect()
+}
+
+/// Splits the input at each separator line, dropping the separators.
+fn split_diagrams(input: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim() == DIAGRAM_SEPARATOR {
+            sections.push(&input[start..offset]);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    sections.push(&input[start..]);
+    sections
+}
+
+/// Parses a diagram whose commands use the given keywords.
+pub fn parse_diagram_with_keywords(input: &str, keywords: &Keywords) -> Diagram {
+    let mut warnings = Vec::new();
+    let diagram = parse_diagram_reporting(input, keywords, &mut warnings);
+    warning::log(&warnings);
+    diagram
+}
+
+/// Parses a diagram, handing the lines whose command isn't a built-in one
+/// to the handler registered for their keyword, e.g. to record an `asset`
+/// or an `owner` with [`Diagram::set_metadata`]. Lines with neither are
+/// skipped.
+pub fn parse_diagram_with_handlers(
+    input: &str,
+    handlers: &mut HashMap<String, CommandHandler<'_>>,
+) -> Diagram {
+    let mut warnings = Vec::new();
+    let diagram = parse_diagram_limited(
+        input,
+        &Keywords::default(),
+        &ParseLimits::NONE,
+        handlers,
+        &mut warnings,
+    )
+    .unwrap_or_default();
+    warning::log(&warnings);
+    diagram
+}
+
+/// Parses a diagram, skipping the lines that can't be parsed and recording
+/// why in `warnings`. Parsing stops at the first separator, as the input
+/// holds several diagrams; see [`parse_diagrams`].
+fn parse_diagram_reporting(
+    input: &str,
+    keywords: &Keywords,
+    warnings: &mut Vec<Warning>,
+) -> Diagram {
+    parse_diagram_limited(
+        input,
+        keywords,
+        &ParseLimits::NONE,
+        &mut HashMap::new(),
+        warnings,
+    )
+    .unwrap_or_default()
+}
+
+/// Parses a diagram like [`parse_diagram_reporting`], failing on the first
+/// line that exceeds `limits`. Unknown commands are handed to `handlers`.
+fn parse_diagram_limited(
+    input: &str,
+    keywords: &Keywords,
+    limits: &ParseLimits,
+    handlers: &mut HashMap<String, CommandHandler<'_>>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Diagram, LimitError> {
     let mut diagram = Diagram::default();
-    let lines = input.lines();
-    for line in lines {
-        let Some((command, value)) = line.split_once(' ') else {
+    let mut state = ParserState::default();
+    let mut attachments = 0usize;
+    let body = front_matter::strip_front_matter(input);
+    // Line numbers count the lines of the front matter too.
+    let first_line = input[..input.len() - body.len()].lines().count() + 1;
+    for (i, line) in body.lines().enumerate() {
+        let line_number = first_line + i;
+        let mut warn = |message: String| {
+            warnings.push(Warning::new(Some(line_number), message));
+        };
+        if line.len() > limits.max_line_length {
+            return Err(LimitError::new(
+                line_number,
+                format!("line longer than {} bytes", limits.max_line_length),
+            ));
+        }
+        // Commands are separated from their values by any run of spaces or
+        // tabs, and lines may be indented.
+        let line = line.trim();
+        if line == DIAGRAM_SEPARATOR {
+            warn("only the first diagram is parsed, see `parse_diagrams`".to_owned());
+            break;
+        }
+        let Some((command, value)) = line.split_once(char::is_whitespace) else {
+            if !line.is_empty() {
+                warn(format!("expected a command and its value, found `{line}`"));
+            }
             continue;
         };
         let value = value.trim();
+        let Some(command) = keywords.command(command) else {
+            match handlers.get_mut(command) {
+                Some(handler) => handler(&mut diagram, value),
+                None => warn(format!("unknown command `{command}`")),
+            }
+            continue;
+        };
         match command {
-            "title" => {
+            Command::Title => {
                 diagram.title = value.to_owned();
             }
-            "cause" => {
-                let is_new = !diagram
-                    .components
-                    .iter()
-                    .any(|c| c.name == value && c.kind == ComponentKind::Cause);
-                if !is_new {
+            Command::Note => {
+                diagram.notes.push(value.to_owned());
+            }
+            Command::Cause | Command::Consequence => {
+                let kind = match command {
+                    Command::Cause => ComponentKind::Cause,
+                    _ => ComponentKind::Consequence,
+                };
+                let component = parse_component(value, kind);
+                let name = component.name.clone();
+                if !diagram.declare_component(component) {
+                    warn(format!(
+                        "duplicate {} `{name}`",
+                        kind.to_string().to_lowercase()
+                    ));
+                }
+                if diagram.components.len() > limits.max_components {
+                    return Err(LimitError::new(
+                        line_number,
+                        format!("more than {} components", limits.max_components),
+                    ));
+                }
+            }
+            Command::Event => {
+                let (event, attributes) = split_attributes(value);
+                for (key, value) in attributes {
+                    match key {
+                        "severity" => {
+                            diagram.event_severity =
+                                value.parse::<u8>().ok().filter(|s| (1..=5).contains(s))
+                        }
+                        _ => continue,
+                    }
+                }
+                diagram.event = event.trim().to_owned();
+            }
+            Command::EventRadius => {
+                let radius = value.trim().parse::<f64>().ok();
+                let Some(radius) = radius.filter(|r| r.is_finite() && *r > 0.0) else {
+                    warn(format!("invalid event radius `{value}`"));
+                    continue;
+                };
+                diagram.event_radius = Some(radius);
+            }
+            Command::Rating => {
+                let Some(rating) = parse_rating(value) else {
+                    warn(format!("invalid rating `{value}`, expected e.g. `L3 S4`"));
                     continue;
+                };
+                diagram.rating = Some(rating);
+            }
+            Command::Defaults => {
+                let Some((setting, value)) = value.split_once(char::is_whitespace) else {
+                    warn(format!("expected a setting and its value, found `{value}`"));
+                    continue;
+                };
+                match setting {
+                    "effectiveness" => {
+                        let Some(effectiveness) = parse_probability(value) else {
+                            warn(format!("invalid effectiveness `{value}`"));
+                            continue;
+                        };
+                        state.default_effectiveness = Some(effectiveness);
+                    }
+                    _ => {
+                        warn(format!("unknown setting `{setting}`"));
+                        continue;
+                    }
+                }
+            }
+            Command::Pathway => {
+                let Some((component_name, label)) = value.split_once(':') else {
+                    warn(missing_colon(value));
+                    continue;
+                };
+                let Some((name, kind)) = diagram.command_target(component_name, keywords) else {
+                    warn(unknown_component(component_name));
+                    continue;
+                };
+                for component in diagram.components_named_mut(name, kind) {
+                    component.pathway = Some(label.trim().to_owned());
                 }
-                let component = Component {
-                    name: value.to_owned(),
-                    barriers: Vec::new(),
-                    kind: ComponentKind::Cause,
+            }
+            Command::Describe => {
+                let Some((component_name, description)) = value.split_once(':') else {
+                    warn(missing_colon(value));
+                    continue;
+                };
+                let Some((name, kind)) = diagram.command_target(component_name, keywords) else {
+                    warn(unknown_component(component_name));
+                    continue;
                 };
-                diagram.components.push(component);
+                for component in diagram.components_named_mut(name, kind) {
+                    component.description = Some(description.trim().to_owned());
+                }
             }
-            "consequence" => {
-                let is_new = !diagram
-                    .components
-                    .iter()
-                    .any(|c| c.name == value && c.kind == ComponentKind::Consequence);
-                if !is_new {
+            Command::Category => {
+                let Some((category, component_names)) = value.split_once(':') else {
+                    warn(missing_colon(value));
                     continue;
+                };
+                let component_names = component_names.split(',').map(|name| name.trim());
+                for component_name in component_names {
+                    let Some((name, kind)) = diagram.command_target(component_name, keywords)
+                    else {
+                        warn(unknown_component(component_name));
+                        continue;
+                    };
+                    for component in diagram.components_named_mut(name, kind) {
+                        component.category = Some(category.trim().to_owned());
+                    }
+                }
+            }
+            Command::Gate => {
+                let Some((component_name, expression)) = value.split_once(':') else {
+                    warn(missing_colon(value));
+                    continue;
+                };
+                let Some((name, kind)) = diagram.command_target(component_name, keywords) else {
+                    warn(unknown_component(component_name));
+                    continue;
+                };
+                let Some(gate) = parse_gate(expression) else {
+                    warn(format!(
+                        "invalid gate `{}`, expected barriers joined by AND or by OR",
+                        expression.trim()
+                    ));
+                    continue;
+                };
+                for component in diagram.components_named_mut(name, kind) {
+                    component.gates.push(gate.clone());
                 }
-                let component = Component {
-                    name: value.to_owned(),
-                    barriers: Vec::new(),
-                    kind: ComponentKind::Consequence,
+            }
+            Command::Icon => {
+                let Some((component_name, icon_name)) = value.rsplit_once(':') else {
+                    warn(missing_colon(value));
+                    continue;
                 };
-                diagram.components.push(component);
+                let Some((name, kind)) = diagram.command_target(component_name, keywords) else {
+                    warn(unknown_component(component_name));
+                    continue;
+                };
+                let Some(icon) = Icon::from_name(icon_name.trim()) else {
+                    warn(format!("unknown icon `{}`", icon_name.trim()));
+                    continue;
+                };
+                for component in diagram.components_named_mut(name, kind) {
+                    component.icon = Some(icon);
+                }
             }
-            "event" => {
-                diagram.event = value.to_owned();
+            Command::Route => {
+                let Some((component_name, waypoints)) = value.rsplit_once(':') else {
+                    warn(missing_colon(value));
+                    continue;
+                };
+                let Some((name, kind)) = diagram.command_target(component_name, keywords) else {
+                    warn(unknown_component(component_name));
+                    continue;
+                };
+                let route = match parse_route(waypoints) {
+                    Ok(route) => route,
+                    Err(waypoint) => {
+                        warn(format!(
+                            "invalid waypoint `{waypoint}`, expected one like `300,400`"
+                        ));
+                        continue;
+                    }
+                };
+                for component in diagram.components_named_mut(name, kind) {
+                    component.route = route.clone();
+                }
             }
-            "barrier" => {
-                let Some((barrier_name, components_name)) = value.split_once(':') else {
+            Command::Barrier | Command::Preventive | Command::Recovery => {
+                // Preventive and recovery barriers only attach to components
+                // of their side, even where a name is used on both.
+                let kind = match command {
+                    Command::Preventive => Some(ComponentKind::Cause),
+                    Command::Recovery => Some(ComponentKind::Consequence),
+                    _ => None,
+                };
+                let Some((mut barrier, component_names, associated_names)) = parse_barrier(value)
+                else {
+                    warn(missing_colon(value));
                     continue;
                 };
-                let barrier_name = barrier_name.trim();
-                let component_names = components_name.trim().split(',').collect::<Vec<_>>();
-                let components = diagram
+                // Defaults apply to new barriers only, so that a repeated
+                // declaration doesn't override an explicit value.
+                if barrier.effectiveness.is_none() && diagram.find_barrier(&barrier.name).is_none()
+                {
+                    barrier.effectiveness = state.default_effectiveness;
+                }
+                let component_names = diagram.expand_wildcards(&component_names, keywords);
+                let component_names = component_names
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                let associated_names = diagram.expand_wildcards(&associated_names, keywords);
+                let associated_names = associated_names
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                let known_names = diagram
                     .components
-                    .iter_mut()
-                    .filter(|c| component_names.iter().any(|name| c.name == name.trim()));
-                for component in components {
-                    component.barriers.push(barrier_name.to_owned());
+                    .iter()
+                    .filter(|c| kind.is_none_or(|kind| c.kind == kind))
+                    .map(|c| c.name.as_str())
+                    .collect::<HashSet<_>>();
+                let unknown_names = component_names
+                    .iter()
+                    .chain(&associated_names)
+                    .filter(|name| !name.is_empty() && !known_names.contains(*name));
+                for name in unknown_names {
+                    warn(match kind {
+                        Some(kind) => {
+                            format!("unknown {} `{name}`", kind.to_string().to_lowercase())
+                        }
+                        None => unknown_component(name),
+                    });
+                }
+                attachments += component_names.len() + associated_names.len();
+                if attachments > limits.max_attachments {
+                    return Err(LimitError::new(
+                        line_number,
+                        format!("more than {} attachments", limits.max_attachments),
+                    ));
+                }
+                diagram.declare_barrier(barrier, &component_names, &associated_names, kind);
+                if diagram.barriers.len() > limits.max_barriers {
+                    return Err(LimitError::new(
+                        line_number,
+                        format!("more than {} barriers", limits.max_barriers),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(diagram)
+}
+
+impl Diagram {
+    /// Parses a diagram from its text description.
+    pub fn parse(input: &str) -> Diagram {
+        parse_diagram(input)
+    }
+
+    pub fn render<R>(&self, renderer: R, options: &Options) -> Vec<u8>
+    where
+        R: Renderer,
+    {
+        Brush::render_diagram_into_bytes(renderer, self, options)
+    }
+
+    /// Renders the diagram like [`Diagram::render`], appending the output
+    /// to `buffer` instead of allocating a new one, e.g. to reuse a buffer
+    /// when rendering many diagrams.
+    pub fn render_into<R>(&self, renderer: R, options: &Options, buffer: &mut Vec<u8>)
+    where
+        R: Renderer,
+    {
+        Brush::render_diagram(renderer, self, options).render_into(buffer)
+    }
+
+    /// Renders the diagram with a single scenario highlighted: the pathway
+    /// from `cause` through the event to `consequence`, with the barriers
+    /// along it. Everything else tied to other components is faded.
+    pub fn render_pathway<R>(
+        &self,
+        cause: &str,
+        consequence: &str,
+        renderer: R,
+        options: &Options,
+    ) -> Vec<u8>
+    where
+        R: Renderer,
+    {
+        Brush::new(self, options)
+            .with_pathway(cause, consequence)
+            .render(renderer)
+            .into_bytes()
+    }
+
+    /// Computes the rectangle actually covered by the drawn content, which
+    /// is usually smaller than the canvas, so callers can crop to it.
+    /// Returns `None` if nothing would be drawn.
+    pub fn tight_bounds(&self, options: &Options) -> Option<Rectangle> {
+        Brush::render_diagram(BoundsRenderer::new(), self, options).bounds()
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Whether there's nothing to draw: no components and no event.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.event.is_empty()
+    }
+
+    /// Number of causes and consequences, together.
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Number of distinct barriers, whichever side they're on.
+    pub fn barrier_count(&self) -> usize {
+        self.barriers.len()
+    }
+
+    /// Iterates over the causes, in declaration order.
+    pub fn causes(&self) -> impl Iterator<Item = &Component> {
+        self.components_of(ComponentKind::Cause)
+    }
+
+    /// Iterates over the consequences, in declaration order.
+    pub fn consequences(&self) -> impl Iterator<Item = &Component> {
+        self.components_of(ComponentKind::Consequence)
+    }
+
+    fn components_of(&self, kind: ComponentKind) -> impl Iterator<Item = &Component> {
+        self.components.iter().filter(move |c| c.kind == kind)
+    }
+
+    /// Lines of the notes written below the diagram, as declared.
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Returns a value set by a custom command.
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Sets a value for custom commands to record, replacing any previous
+    /// one under the same key.
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_owned(), value.to_owned());
+    }
+
+    /// Returns the names of the barriers protecting a component, in the
+    /// order they were attached. A cause and a consequence may share a name,
+    /// so components are matched by kind too. Returns an empty list for an
+    /// unknown component.
+    pub fn barriers_for(&self, component_name: &str, kind: ComponentKind) -> Vec<&str> {
+        self.components
+            .iter()
+            .find(|c| c.name == component_name && c.kind == kind)
+            .map(|c| c.barriers.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Counts the distinct preventive and recovery barriers, those on
+    /// causes and on consequences, which set how wide each side's barrier
+    /// columns are. A barrier on both sides counts on each. Together with
+    /// [`Layout::canvas_width`], this helps decide on stacking barriers via
+    /// [`Options::barrier_stack_threshold`] or splitting a diagram up.
+    pub fn barrier_counts(&self) -> (usize, usize) {
+        let count = |kind| filter_barriers(&filter_components(self, kind)).len();
+        (
+            count(ComponentKind::Cause),
+            count(ComponentKind::Consequence),
+        )
+    }
+
+    /// Whether a component of either kind has the given name.
+    fn has_component(&self, name: &str) -> bool {
+        self.has_component_of(name, None)
+    }
+
+    /// Resolves the component named in a command such as `icon`: every
+    /// component of the name, or, for a name used on both sides, only the
+    /// one of the kind it is qualified with, as in `cause Fire`. Names of
+    /// components are matched as they are before being read as qualified.
+    fn command_target<'a>(
+        &self,
+        name: &'a str,
+        keywords: &Keywords,
+    ) -> Option<(&'a str, Option<ComponentKind>)> {
+        let name = name.trim();
+        if self.has_component(name) {
+            return Some((name, None));
+        }
+        let (keyword, rest) = name.split_once(char::is_whitespace)?;
+        let kind = if keyword == keywords.cause {
+            ComponentKind::Cause
+        } else if keyword == keywords.consequence {
+            ComponentKind::Consequence
+        } else {
+            return None;
+        };
+        let rest = rest.trim();
+        self.has_component_of(rest, Some(kind))
+            .then_some((rest, Some(kind)))
+    }
+
+    /// Components of the given name, only those of `kind` if given.
+    fn components_named_mut<'a>(
+        &'a mut self,
+        name: &'a str,
+        kind: Option<ComponentKind>,
+    ) -> impl Iterator<Item = &'a mut Component> {
+        self.components
+            .iter_mut()
+            .filter(move |c| c.name == name && kind.is_none_or(|kind| c.kind == kind))
+    }
+
+    /// Whether a component of the given kind, or of either if `None`, has
+    /// the given name.
+    fn has_component_of(&self, name: &str, kind: Option<ComponentKind>) -> bool {
+        self.components
+            .iter()
+            .any(|c| c.name == name && kind.is_none_or(|kind| c.kind == kind))
+    }
+
+    /// Computes the likelihood of a cause once its barriers act on it: its
+    /// likelihood times the chance that each barrier fails, one minus its
+    /// effectiveness. Barriers without an effectiveness, or absent ones,
+    /// don't reduce it.
+    /// Returns `None` for an unknown cause or one without a likelihood.
+    pub fn residual_likelihood(&self, cause: &str) -> Option<f64> {
+        let cause = self
+            .components
+            .iter()
+            .find(|c| c.name == cause && c.kind == ComponentKind::Cause)?;
+        let failure = cause
+            .barriers
+            .iter()
+            .filter_map(|b| self.find_barrier(b)?.counted_effectiveness())
+            .map(|effectiveness| 1.0 - effectiveness)
+            .product::<f64>();
+        Some(cause.likelihood? * failure)
+    }
+
+    fn find_barrier(&self, name: &str) -> Option<&Barrier> {
+        self.barriers.iter().find(|b| b.name == name)
+    }
+
+    /// Adds a component unless one of the same name and kind exists.
+    /// Returns whether the component was added.
+    fn declare_component(&mut self, component: Component) -> bool {
+        let is_new = !self
+            .components
+            .iter()
+            .any(|c| c.name == component.name && c.kind == component.kind);
+        if is_new {
+            self.components.push(component);
+        }
+        is_new
+    }
+
+    /// Replaces `*cause` and `*consequence`, written with the cause and
+    /// consequence keywords, with the names of all the components of that
+    /// kind declared so far. Each name is listed once, however many times
+    /// it or a wildcard matching it is repeated.
+    fn expand_wildcards(&self, names: &[&str], keywords: &Keywords) -> Vec<String> {
+        let mut expanded = Vec::new();
+        // Names already listed, so that repeated names and wildcards, which
+        // would attach nothing new, don't add to the work of attaching.
+        let mut seen = HashSet::new();
+        let mut expanded_kinds = Vec::new();
+        for name in names {
+            let kind = match name.strip_prefix('*') {
+                Some(kind) if kind == keywords.cause => ComponentKind::Cause,
+                Some(kind) if kind == keywords.consequence => ComponentKind::Consequence,
+                _ => {
+                    if seen.insert(*name) {
+                        expanded.push(name.to_string());
+                    }
+                    continue;
+                }
+            };
+            if expanded_kinds.contains(&kind) {
+                continue;
+            }
+            expanded_kinds.push(kind);
+            for component in self.components.iter().filter(|c| c.kind == kind) {
+                if seen.insert(&component.name) {
+                    expanded.push(component.name.clone());
                 }
             }
-            _ => {
+        }
+        expanded
+    }
+
+    /// Registers a barrier and attaches it to the named components, only
+    /// those of `kind` if given, and associates it with the components of
+    /// `associated_names`, without a box on their connectors. Repeated
+    /// declarations of the same barrier are merged into one, accumulating
+    /// the components it protects; attaching a barrier with a box to a
+    /// component it was associated with gives it the box.
+    fn declare_barrier(
+        &mut self,
+        barrier: Barrier,
+        component_names: &[&str],
+        associated_names: &[&str],
+        kind: Option<ComponentKind>,
+    ) {
+        let component_names = component_names.iter().copied().collect::<HashSet<_>>();
+        let associated_names = associated_names.iter().copied().collect::<HashSet<_>>();
+        // A new barrier isn't attached to any component yet, which saves
+        // looking for it in the barriers of each one.
+        let is_new = self.find_barrier(&barrier.name).is_none();
+        for component in &mut self.components {
+            if kind.is_some_and(|kind| component.kind != kind) {
                 continue;
             }
+            let name = component.name.as_str();
+            let is_attached = !is_new && component.barriers.contains(&barrier.name);
+            if component_names.contains(name) {
+                if !is_new {
+                    component.associated_barriers.retain(|b| *b != barrier.name);
+                }
+            } else if !associated_names.contains(name) {
+                continue;
+            } else if !is_attached {
+                component.associated_barriers.push(barrier.name.clone());
+            }
+            if !is_attached {
+                component.barriers.push(barrier.name.clone());
+            }
+        }
+        match self.barriers.iter_mut().find(|b| b.name == barrier.name) {
+            Some(existing) => existing.merge(barrier),
+            None => self.barriers.push(barrier),
+        }
+    }
+}
+
+impl Component {
+    /// Creates a component with only a name, without barriers.
+    fn new(name: &str, kind: ComponentKind) -> Self {
+        Component {
+            name: name.to_owned(),
+            barriers: Vec::new(),
+            associated_barriers: Vec::new(),
+            kind,
+            score: None,
+            likelihood: None,
+            pathway: None,
+            description: None,
+            category: None,
+            icon: None,
+            gates: Vec::new(),
+            route: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> ComponentKind {
+        self.kind
+    }
+}
+
+/// Formats as "Cause" or "Consequence". The alternate form, `{:#}`, uses
+/// "Threat" for causes, as some methodologies do.
+impl fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match (self, f.alternate()) {
+            (ComponentKind::Cause, false) => "Cause",
+            (ComponentKind::Cause, true) => "Threat",
+            (ComponentKind::Consequence, _) => "Consequence",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Parses either name of a kind, ignoring case.
+impl FromStr for ComponentKind {
+    type Err = ParseComponentKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cause" | "threat" => Ok(ComponentKind::Cause),
+            "consequence" => Ok(ComponentKind::Consequence),
+            _ => Err(ParseComponentKindError(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for ParseComponentKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown component kind: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseComponentKindError {}
+
+impl Barrier {
+    /// Creates a barrier with only a name.
+    #[cfg(feature = "xlsx")]
+    fn new(name: &str) -> Self {
+        Barrier {
+            name: name.to_owned(),
+            group: None,
+            state: None,
+            effectiveness: None,
+            central: false,
+            condition: None,
+            absent: false,
+        }
+    }
+
+    /// Merges a later declaration into this one. Attributes set by the later
+    /// declaration take precedence, while attributes it leaves unset keep
+    /// their earlier value.
+    fn merge(&mut self, other: Barrier) {
+        if other.group.is_some() {
+            self.group = other.group;
+        }
+        if other.state.is_some() {
+            self.state = other.state;
+        }
+        if other.effectiveness.is_some() {
+            self.effectiveness = other.effectiveness;
+        }
+        self.central |= other.central;
+        if other.condition.is_some() {
+            self.condition = other.condition;
+        }
+        self.absent |= other.absent;
+    }
+
+    fn state(&self) -> BarrierState {
+        self.state.unwrap_or_default()
+    }
+
+    /// Effectiveness counted in calculations, none for an absent barrier,
+    /// as it stops nothing.
+    fn counted_effectiveness(&self) -> Option<f64> {
+        if self.absent {
+            None
+        } else {
+            self.effectiveness
+        }
+    }
+}
+
+/// Parses a component declaration in the form
+/// `name | score 87 | likelihood 0.3`.
+fn parse_component(value: &str, kind: ComponentKind) -> Component {
+    let (name, attributes) = split_attributes(value);
+    let mut score = None;
+    let mut likelihood = None;
+    for (key, value) in attributes {
+        match key {
+            "score" => {
+                score = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|s| (0.0..=100.0).contains(s))
+            }
+            "likelihood" => likelihood = parse_probability(value),
+            _ => continue,
+        }
+    }
+    Component {
+        score,
+        likelihood,
+        ..Component::new(name.trim(), kind)
+    }
+}
+
+/// Parses a barrier declaration in the form
+/// `name [state] ?{if condition} #group | attribute value: component, ...`,
+/// returning the barrier, the components it's attached to and those it's
+/// only associated with.
+fn parse_barrier(value: &str) -> Option<(Barrier, Vec<&str>, Vec<&str>)> {
+    let (barrier_name, components_name) = value.split_once(':')?;
+    let (barrier_name, attributes) = split_attributes(barrier_name);
+    let mut effectiveness = None;
+    let mut central = false;
+    for (key, value) in attributes {
+        match key {
+            "effectiveness" => effectiveness = parse_probability(value),
+            "central" => central = true,
+            _ => continue,
+        }
+    }
+    let (barrier_name, condition) = take_delimited(barrier_name, "?{", "}");
+    let condition = condition.map(parse_condition);
+    let (barrier_name, state) = take_delimited(&barrier_name, "[", "]");
+    let state = state.and_then(parse_barrier_state);
+    let (barrier_name, group) = match barrier_name.split_once('#') {
+        Some((name, group)) => (name.trim(), Some(group.trim().to_owned())),
+        None => (barrier_name.trim(), None),
+    };
+    let (barrier_name, absent) = match barrier_name.strip_prefix('~') {
+        Some(name) => (name.trim(), true),
+        None => (barrier_name, false),
+    };
+    // Components with a leading `+` are only associated with the barrier.
+    let (associated_names, component_names) = components_name
+        .trim()
+        .split(',')
+        .map(|name| name.trim())
+        .partition::<Vec<_>, _>(|name| name.starts_with('+'));
+    let associated_names = associated_names
+        .into_iter()
+        .map(|name| name[1..].trim_start())
+        .collect();
+    let barrier = Barrier {
+        name: barrier_name.to_owned(),
+        group,
+        state,
+        effectiveness,
+        central,
+        condition,
+        absent,
+    };
+    Some((barrier, component_names, associated_names))
+}
+
+fn missing_colon(value: &str) -> String {
+    format!("missing `:` in `{value}`")
+}
+
+fn unknown_component(name: &str) -> String {
+    format!("unknown component `{}`", name.trim())
+}
+
+/// Parses the condition under which a barrier applies, dropping the
+/// leading "if", which is added back when rendering.
+fn parse_condition(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix("if ")
+        .unwrap_or(value)
+        .trim_start()
+        .to_owned()
+}
+
+/// Parses a probability, such as an effectiveness, from 0 to 1.
+fn parse_probability(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|e| (0.0..=1.0).contains(e))
+}
+
+/// Splits trailing `| key value` attributes off a declaration, returning
+/// the declaration and its attributes in order.
+fn split_attributes(value: &str) -> (&str, Vec<(&str, &str)>) {
+    let mut parts = value.split('|');
+    let declaration = parts.next().unwrap_or_default();
+    let attributes = parts
+        .map(|attribute| {
+            let attribute = attribute.trim();
+            let (key, value) = attribute
+                .split_once(char::is_whitespace)
+                .unwrap_or((attribute, ""));
+            (key, value.trim())
+        })
+        .filter(|(key, _)| !key.is_empty())
+        .collect();
+    (declaration, attributes)
+}
+
+fn parse_barrier_state(value: &str) -> Option<BarrierState> {
+    match value.trim().to_lowercase().as_str() {
+        "effective" => Some(BarrierState::Effective),
+        "degraded" => Some(BarrierState::Degraded),
+        "failed" => Some(BarrierState::Failed),
+        _ => None,
+    }
+}
+
+/// Removes the first `open`...`close` delimited section from `value`,
+/// returning the remaining text and the section's content.
+fn take_delimited<'a>(value: &'a str, open: &str, close: &str) -> (String, Option<&'a str>) {
+    let Some(start) = value.find(open) else {
+        return (value.to_owned(), None);
+    };
+    let Some(length) = value[start..].find(close) else {
+        return (value.to_owned(), None);
+    };
+    let end = start + length;
+    let content = &value[start + open.len()..end];
+    let remaining = format!("{}{}", &value[..start], &value[end + close.len()..]);
+    (remaining, Some(content))
+}
+
+/// Parses a gate in the form `Valve AND Alarm` or `Valve OR Alarm`. A gate
+/// joins barriers with a single operator.
+fn parse_gate(value: &str) -> Option<Gate> {
+    let operators = [(GateOperator::And, " AND "), (GateOperator::Or, " OR ")];
+    let (operator, separator) = operators
+        .into_iter()
+        .find(|(_, separator)| value.contains(separator))?;
+    let barriers = value
+        .split(separator)
+        .map(|barrier| barrier.trim().to_owned())
+        .collect::<Vec<_>>();
+    let is_mixed = barriers
+        .iter()
+        .any(|b| b.is_empty() || operators.iter().any(|(_, s)| b.contains(s)));
+    if is_mixed {
+        return None;
+    }
+    Some(Gate { operator, barriers })
+}
+
+impl GateOperator {
+    fn as_str(&self) -> &'static str {
+        match self {
+            GateOperator::And => "AND",
+            GateOperator::Or => "OR",
+        }
+    }
+}
+
+/// Parses waypoints in the form `300,400; 350,420`, failing with the first
+/// one that isn't a pair of finite coordinates.
+fn parse_route(value: &str) -> Result<Vec<Vector2>, &str> {
+    value
+        .split(';')
+        .map(|point| parse_waypoint(point).ok_or(point.trim()))
+        .collect()
+}
+
+fn parse_waypoint(point: &str) -> Option<Vector2> {
+    let (x, y) = point.split_once(',')?;
+    let coordinate = |value: &str| value.trim().parse().ok().filter(|c: &f64| c.is_finite());
+    Some(Vector2 {
+        x: coordinate(x)?,
+        y: coordinate(y)?,
+    })
+}
+
+/// Parses a rating in the form `L3 S4`.
+fn parse_rating(value: &str) -> Option<Rating> {
+    let mut likelihood = None;
+    let mut severity = None;
+    for token in value.split_whitespace() {
+        let mut chars = token.chars();
+        let prefix = chars.next()?;
+        let level = chars.as_str().parse::<u8>().ok();
+        let level = level.filter(|l| (1..=5).contains(l))?;
+        match prefix.to_ascii_uppercase() {
+            'L' => likelihood = Some(level),
+            'S' => severity = Some(level),
+            _ => return None,
+        }
+    }
+    Some(Rating {
+        likelihood: likelihood?,
+        severity: severity?,
+    })
+}
+
+impl Rating {
+    /// Looks up the cell colour of a standard 5x5 risk matrix.
+    fn color(&self) -> Color {
+        match self.likelihood * self.severity {
+            1..=4 => Color::rgb(0x4c, 0xaf, 0x50),
+            5..=9 => Color::rgb(0xff, 0xeb, 0x3b),
+            10..=16 => Color::rgb(0xff, 0x98, 0x00),
+            _ => Color::rgb(0xf4, 0x43, 0x36),
         }
     }
-    diagram
 }
@@ -0,0 +1,89 @@
+use crate::keywords::Keywords;
+use crate::{parse_diagram_limited, warning, Diagram};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Bounds on the size of input parsed by [`Diagram::parse_with_limits`],
+/// for input from untrusted sources. With the length of each line bounded,
+/// so is the work done for it, e.g. the components a barrier lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum length of a line, in bytes.
+    pub max_line_length: usize,
+    /// Maximum number of causes and consequences, together.
+    pub max_components: usize,
+    /// Maximum number of distinct barriers.
+    pub max_barriers: usize,
+    /// Maximum number of components named by barrier declarations, summed
+    /// over all of them, with wildcards such as `*cause` counted once
+    /// expanded.
+    pub max_attachments: usize,
+}
+
+/// Error returned when input exceeds one of its [`ParseLimits`], with the
+/// line of the input it was exceeded on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitError {
+    line: usize,
+    message: String,
+}
+
+impl ParseLimits {
+    /// Limits that no input exceeds, for trusted input.
+    pub(crate) const NONE: ParseLimits = ParseLimits {
+        max_line_length: usize::MAX,
+        max_components: usize::MAX,
+        max_barriers: usize::MAX,
+        max_attachments: usize::MAX,
+    };
+}
+
+impl Default for ParseLimits {
+    /// Generous limits for diagrams written by hand.
+    fn default() -> Self {
+        ParseLimits {
+            max_line_length: 4096,
+            max_components: 500,
+            max_barriers: 1000,
+            max_attachments: 20_000,
+        }
+    }
+}
+
+impl LimitError {
+    pub(crate) fn new(line: usize, message: String) -> Self {
+        LimitError { line, message }
+    }
+
+    /// Line of the input the limit was exceeded on, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl Diagram {
+    /// Parses a diagram like [`Diagram::parse`], failing as soon as the
+    /// input exceeds `limits`, so that untrusted input can't make parsing
+    /// take unbounded time or memory. The caller is expected to bound the
+    /// length of the input as a whole.
+    pub fn parse_with_limits(input: &str, limits: &ParseLimits) -> Result<Diagram, LimitError> {
+        let mut warnings = Vec::new();
+        let diagram = parse_diagram_limited(
+            input,
+            &Keywords::default(),
+            limits,
+            &mut HashMap::new(),
+            &mut warnings,
+        );
+        warning::log(&warnings);
+        diagram
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Diagram;
+
+/// Decides what happens to a component present in both diagrams being merged.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MergePolicy {
+    /// Keeps the existing component and ignores the other one.
+    Skip,
+    /// Keeps the existing component and adds the barriers of the other one.
+    UnionBarriers,
+    /// Replaces the existing component with the other one.
+    PreferOther,
+}
+
+/// Names of the components affected by a merge.
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// Components that only existed in the other diagram.
+    pub added: Vec<String>,
+    /// Components present in both diagrams that were combined or replaced.
+    pub merged: Vec<String>,
+    /// Components present in both diagrams that were left untouched.
+    pub skipped: Vec<String>,
+}
+
+impl Diagram {
+    /// Merges another diagram into this one. The title, event, event
+    /// radius, rating, notes and metadata values of this diagram are kept
+    /// unless they are unset. Barriers declared in both diagrams keep their
+    /// existing attributes, unless the policy prefers the other diagram.
+    /// Barriers of the other diagram are only brought over if a component
+    /// kept by the merge is protected by them, and barriers left protecting
+    /// nothing by a replaced component are dropped.
+    pub fn merge(&mut self, other: Diagram, policy: MergePolicy) -> MergeReport {
+        let mut report = MergeReport::default();
+        if self.title.is_empty() {
+            self.title = other.title;
+        }
+        if self.event.is_empty() {
+            self.event = other.event;
+            self.event_severity = other.event_severity;
+        }
+        if self.event_radius.is_none() {
+            self.event_radius = other.event_radius;
+        }
+        if self.rating.is_none() {
+            self.rating = other.rating;
+        }
+        if self.notes.is_empty() {
+            self.notes = other.notes;
+        }
+        for (key, value) in other.metadata {
+            self.metadata.entry(key).or_insert(value);
+        }
+        // Barriers that may be left protecting nothing once merged.
+        let mut unattached = Vec::new();
+        for barrier in other.barriers {
+            match self.barriers.iter_mut().find(|b| b.name == barrier.name) {
+                Some(existing) if policy == MergePolicy::PreferOther => existing.merge(barrier),
+                Some(_) => {}
+                None => {
+                    unattached.push(barrier.name.clone());
+                    self.barriers.push(barrier);
+                }
+            }
+        }
+        for component in other.components {
+            let existing = self
+                .components
+                .iter_mut()
+                .find(|c| c.name == component.name && c.kind == component.kind);
+            let Some(existing) = existing else {
+                report.added.push(component.name.clone());
+                self.declare_component(component);
+                continue;
+            };
+            match policy {
+                MergePolicy::Skip => {
+                    report.skipped.push(component.name);
+                }
+                MergePolicy::UnionBarriers => {
+                    for barrier in component.barriers {
+                        if existing.barriers.contains(&barrier) {
+                            continue;
+                        }
+                        if component.associated_barriers.contains(&barrier) {
+                            existing.associated_barriers.push(barrier.clone());
+                        }
+                        existing.barriers.push(barrier);
+                    }
+                    report.merged.push(component.name);
+                }
+                MergePolicy::PreferOther => {
+                    report.merged.push(component.name.clone());
+                    unattached.append(&mut existing.barriers);
+                    *existing = component;
+                }
+            }
+        }
+        let components = &self.components;
+        self.barriers.retain(|barrier| {
+            !unattached.contains(&barrier.name)
+                || components
+                    .iter()
+                    .any(|c| c.barriers.contains(&barrier.name))
+        });
+        report
+    }
+}
@@ -0,0 +1,283 @@
+use crate::renderer::{Color, LineCap, LineJoin, RendererSettings};
+
+/// Layout options applied when drawing a diagram.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Minimum canvas width. Smaller diagrams are centered within it.
+    pub min_canvas_width: f64,
+    /// Minimum canvas height. Smaller diagrams are centered within it.
+    pub min_canvas_height: f64,
+    /// Exact canvas size, overriding the computed one and the minimums. The
+    /// diagram is centered within it, and scaled down uniformly if it
+    /// wouldn't fit.
+    pub canvas_size: Option<(f64, f64)>,
+    /// Maximum canvas width. Wider diagrams are fitted by shrinking their
+    /// fonts, and with them the text boxes, while strokes keep their width.
+    pub max_canvas_width: Option<f64>,
+    /// Canvas size beyond which, in either dimension, a warning suggests
+    /// splitting the diagram up or stacking its barriers. The diagram is
+    /// still drawn at its full size.
+    pub canvas_size_limit: Option<(f64, f64)>,
+    /// Minimum radius of the event circle, so that short events still read
+    /// as the central node.
+    pub min_event_radius: f64,
+    /// Keeps the event circle a fixed size, at least `min_event_radius`,
+    /// and writes an event that doesn't fit inside it as a caption below,
+    /// instead of growing the circle to fit.
+    pub event_caption: bool,
+    /// Label drawn, styled as a placeholder, in the event circle of a
+    /// diagram without an event, "Top Event" by default.
+    pub event_placeholder: Option<String>,
+    /// Renders only one side of the bowtie, anchored on the event.
+    pub side: Option<Side>,
+    /// How far connectors fan out along the event circle, from 0 (all
+    /// meet at a single point) to 1 (each points straight at its component).
+    pub connector_spread: f64,
+    /// Order of the barrier columns of each side.
+    pub barrier_order: BarrierOrder,
+    /// Vertical alignment of the cause and consequence columns against
+    /// each other.
+    pub component_alignment: ComponentAlignment,
+    /// Where the names of barriers are written.
+    pub barrier_label_placement: BarrierLabelPlacement,
+    /// Width and height of the barrier boxes drawn on the connectors, tall
+    /// and narrow by default. Some templates use wide, short boxes instead.
+    pub barrier_box_size: Option<(f64, f64)>,
+    /// Draws each barrier as a single rectangle spanning all the connectors
+    /// it protects, instead of a box on each connector.
+    pub merge_barrier_boxes: bool,
+    /// Highlights barriers protecting more than one component of a side,
+    /// which are single points of reliance.
+    pub highlight_shared_barriers: bool,
+    /// Width of a side's barrier columns above which its barriers are
+    /// instead listed in a vertical panel, joined to their connectors by
+    /// leader lines.
+    pub barrier_stack_threshold: Option<f64>,
+    /// Shape of the ends of connector lines.
+    pub connector_line_cap: Option<LineCap>,
+    /// Shape of the corners of connector lines.
+    pub connector_line_join: Option<LineJoin>,
+    /// Colours each component's connector from a palette, cycled by the
+    /// component's position, so a pathway can be followed through its
+    /// barriers. The same diagram is always coloured alike.
+    pub color_connectors: bool,
+    /// Range of stroke widths, from a likelihood of 0 to one of 1, that
+    /// each cause's connector is drawn with in proportion to the cause's
+    /// likelihood, so the most probable pathways stand out. Causes without
+    /// a likelihood, and consequences, keep the renderer's width.
+    pub likelihood_connector_widths: Option<(f64, f64)>,
+    /// Draws a thin dashed border around the canvas, to help verify the
+    /// layout.
+    pub debug_border: bool,
+    /// Draws a light coordinate grid with the given spacing behind the
+    /// diagram, to help verify the layout.
+    pub grid_spacing: Option<f64>,
+    /// Draws a faint vertical line from top to bottom through the event,
+    /// behind the diagram, separating the preventive side from the
+    /// recovery side. It has the `spine` class for styling with CSS.
+    pub spine: bool,
+    /// Colour of the spine, a light grey by default.
+    pub spine_color: Option<Color>,
+    /// Maximum number of characters of component and barrier labels.
+    /// Longer labels are cut short with an ellipsis, and their full text is
+    /// kept as a tooltip.
+    pub max_label_chars: Option<usize>,
+    /// Numbers components in the top-right corner of their boxes, in the
+    /// order they are drawn, e.g. C1, C2 for causes and Q1, Q2 for
+    /// consequences.
+    pub number_components: bool,
+    pub component_number_prefixes: ComponentNumberPrefixes,
+    /// Draws badges counting the components of each side, and the
+    /// components each barrier column protects, above the columns.
+    pub count_badges: bool,
+    /// Writes the residual likelihood of each cause below its box. See
+    /// [`crate::Diagram::residual_likelihood`].
+    pub show_residual_likelihood: bool,
+    /// Scores at which components turn from green to amber and red.
+    pub score_thresholds: ScoreThresholds,
+    /// Text drawn faintly in the bottom-right corner, over the margin.
+    pub watermark: Option<String>,
+    /// Adds the generation time to the watermark.
+    #[cfg(feature = "timestamp")]
+    pub timestamp_watermark: bool,
+    /// Draws consequences on the left and causes on the right.
+    pub mirror: bool,
+    pub font_sizes: FontSizes,
+    /// Gaps between the components and around them. See [`Density`] for
+    /// presets.
+    pub spacing: Spacing,
+    /// Settings handed to the renderer, such as its font family, overriding
+    /// those it was built with.
+    pub renderer_settings: RendererSettings,
+}
+
+/// Font size of each category of text.
+#[derive(Clone, Copy, Debug)]
+pub struct FontSizes {
+    pub title: f64,
+    pub event: f64,
+    pub component: f64,
+    pub barrier: f64,
+    pub pathway: f64,
+}
+
+impl Default for FontSizes {
+    fn default() -> Self {
+        FontSizes {
+            title: 24.0,
+            event: 18.0,
+            component: 18.0,
+            barrier: 18.0,
+            pathway: 14.0,
+        }
+    }
+}
+
+impl FontSizes {
+    pub(crate) fn scaled(&self, factor: f64) -> FontSizes {
+        FontSizes {
+            title: self.title * factor,
+            event: self.event * factor,
+            component: self.component * factor,
+            barrier: self.barrier * factor,
+            pathway: self.pathway * factor,
+        }
+    }
+}
+
+/// Gaps between the elements of the layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spacing {
+    /// Vertical gap between components, and between the rows of the
+    /// barrier label list.
+    pub component_margin: f64,
+    /// Horizontal gap between the components and the edge of the content.
+    pub component_padding: f64,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Density::Normal.spacing()
+    }
+}
+
+/// Presets of [`Spacing`], from tight to airy.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Density {
+    Compact,
+    #[default]
+    Normal,
+    Spacious,
+}
+
+impl Density {
+    pub fn spacing(self) -> Spacing {
+        let (component_margin, component_padding) = match self {
+            Density::Compact => (8.0, 4.0),
+            Density::Normal => (20.0, 10.0),
+            Density::Spacious => (36.0, 20.0),
+        };
+        Spacing {
+            component_margin,
+            component_padding,
+        }
+    }
+}
+
+/// Presets of the colouring options, from plain to colourful.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Theme {
+    /// Leaves the connectors and shared barriers uncoloured and the spine
+    /// out, as by default.
+    #[default]
+    Plain,
+    /// Colours the connectors and shared barriers, and draws the spine.
+    Colorful,
+}
+
+impl Theme {
+    /// Sets the colouring options of the preset, leaving the others alone.
+    pub fn apply(self, options: &mut Options) {
+        let colorful = self == Theme::Colorful;
+        options.color_connectors = colorful;
+        options.highlight_shared_barriers = colorful;
+        options.spine = colorful;
+    }
+}
+
+/// Prefixes of the reference numbers of each kind of component.
+#[derive(Clone, Debug)]
+pub struct ComponentNumberPrefixes {
+    pub cause: String,
+    pub consequence: String,
+}
+
+impl Default for ComponentNumberPrefixes {
+    fn default() -> Self {
+        ComponentNumberPrefixes {
+            cause: "C".to_owned(),
+            consequence: "Q".to_owned(),
+        }
+    }
+}
+
+/// Thresholds mapping a 0 to 100 risk score to a colour: below `amber` is
+/// green, from `amber` up to `red` is amber, and above `red` is red.
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreThresholds {
+    pub amber: f64,
+    pub red: f64,
+}
+
+impl Default for ScoreThresholds {
+    fn default() -> Self {
+        ScoreThresholds {
+            amber: 30.0,
+            red: 70.0,
+        }
+    }
+}
+
+/// Order of the barrier columns, from the components towards the event.
+/// Barriers of the same group stay in adjacent columns either way.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BarrierOrder {
+    /// Barriers protecting the most components first.
+    #[default]
+    Frequency,
+    /// Most effective barriers first, nearest the components. Barriers
+    /// without an effectiveness come last, and ties keep their declaration
+    /// order.
+    Effectiveness,
+}
+
+/// Vertical alignment of the two component columns when the sides have
+/// different numbers of components.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ComponentAlignment {
+    /// Each column is centered on the event.
+    #[default]
+    Center,
+    /// The first rows of both columns line up.
+    Top,
+    /// The last rows of both columns line up.
+    Bottom,
+}
+
+/// Placement of the names of the barriers of each side.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BarrierLabelPlacement {
+    /// A numbered list below the components, referring to the ids above
+    /// the barrier columns.
+    #[default]
+    List,
+    /// Written vertically along each barrier's own column, below the
+    /// components. Sides whose barriers are stacked keep the list.
+    Column,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Side {
+    Causes,
+    Consequences,
+}
@@ -0,0 +1,150 @@
+use crate::renderer::{
+    text_origin, Alignment, Icon, Rectangle, Renderer, Style, Vector2, DEFAULT_FONT_SIZE,
+    FONT_WIDTH,
+};
+
+/// Classes of elements that span the canvas rather than the content.
+const IGNORED_CLASSES: [&str; 3] = ["background", "border", "grid"];
+
+/// Measures the extents of everything drawn, without producing any output.
+/// Elements without a class, or marked as part of the canvas, are ignored.
+#[derive(Default)]
+pub(crate) struct BoundsRenderer {
+    min: Option<Vector2>,
+    max: Option<Vector2>,
+}
+
+impl BoundsRenderer {
+    pub fn new() -> Self {
+        BoundsRenderer::default()
+    }
+
+    /// Returns the rectangle containing all measured elements, if any.
+    pub fn bounds(&self) -> Option<Rectangle> {
+        let (min, max) = (self.min?, self.max?);
+        Some(Rectangle {
+            centre: Vector2 {
+                x: (min.x + max.x) / 2.0,
+                y: (min.y + max.y) / 2.0,
+            },
+            width: max.x - min.x,
+            height: max.y - min.y,
+        })
+    }
+
+    fn include(&mut self, style: &Style, points: &[Vector2]) {
+        let is_content = style
+            .class
+            .is_some_and(|class| !IGNORED_CLASSES.contains(&class));
+        if !is_content {
+            return;
+        }
+        for point in points {
+            let min = self.min.get_or_insert(*point);
+            min.x = min.x.min(point.x);
+            min.y = min.y.min(point.y);
+            let max = self.max.get_or_insert(*point);
+            max.x = max.x.max(point.x);
+            max.y = max.y.max(point.y);
+        }
+    }
+}
+
+impl Renderer for BoundsRenderer {
+    fn setup(self, _width: f64, _height: f64) -> Self {
+        self
+    }
+
+    fn draw_line(mut self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        self.include(style, &[*from, *to]);
+        self
+    }
+
+    fn draw_polyline(mut self, points: &[Vector2], style: &Style) -> Self {
+        self.include(style, points);
+        self
+    }
+
+    fn draw_circle(mut self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let rectangle = Rectangle {
+            centre: *centre,
+            width: radius * 2.0,
+            height: radius * 2.0,
+        };
+        self.include(style, &corners(&rectangle));
+        self
+    }
+
+    fn draw_text(
+        mut self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+        let origin = text_origin(text, font_size, containment, alignment);
+        let width = text.len() as f64 * font_size / FONT_WIDTH;
+        // The text spans a font size above its baseline.
+        let rectangle = Rectangle {
+            centre: Vector2 {
+                x: origin.x + width / 2.0,
+                y: origin.y - font_size / 2.0,
+            },
+            width,
+            height: font_size,
+        };
+        let (sin, cos) = style.rotation.to_radians().sin_cos();
+        let centre = containment.centre;
+        let points = corners(&rectangle).map(|p| {
+            let dx = p.x - centre.x;
+            let dy = p.y - centre.y;
+            Vector2 {
+                x: centre.x + dx * cos - dy * sin,
+                y: centre.y + dx * sin + dy * cos,
+            }
+        });
+        self.include(style, &points);
+        self
+    }
+
+    fn draw_rectangle(mut self, rectangle: &Rectangle, style: &Style) -> Self {
+        self.include(style, &corners(rectangle));
+        self
+    }
+
+    fn draw_text_with_rectangle(
+        mut self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        self = self.draw_rectangle(&rectangle.with_padding(2.0), style);
+        self.draw_text(text, rectangle, alignment, style)
+    }
+
+    fn draw_icon(self, _icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        self.draw_rectangle(rectangle, style)
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+fn corners(rectangle: &Rectangle) -> [Vector2; 4] {
+    let half_width = rectangle.width / 2.0;
+    let half_height = rectangle.height / 2.0;
+    let centre = rectangle.centre;
+    [
+        (-half_width, -half_height),
+        (half_width, -half_height),
+        (half_width, half_height),
+        (-half_width, half_height),
+    ]
+    .map(|(dx, dy)| Vector2 {
+        x: centre.x + dx,
+        y: centre.y + dy,
+    })
+}
@@ -0,0 +1,127 @@
+use crate::renderer::{Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2};
+
+/// Flips the y axis of everything drawn through it, turning the layout's
+/// top-left origin with y growing downwards into a bottom-left origin with
+/// y growing upwards, for consumers of the layout using that convention,
+/// e.g. plotting tools reading the JSON layout.
+///
+/// Only positions are flipped: text and icons are still passed upright,
+/// and rotations are negated to match the flipped axis.
+pub struct FlippedRenderer<R> {
+    inner: R,
+    height: f64,
+}
+
+impl<R> FlippedRenderer<R>
+where
+    R: Renderer,
+{
+    pub fn new(inner: R) -> Self {
+        FlippedRenderer { inner, height: 0.0 }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn point(&self, v: &Vector2) -> Vector2 {
+        Vector2 {
+            x: v.x,
+            y: self.height - v.y,
+        }
+    }
+
+    fn rectangle(&self, rectangle: &Rectangle) -> Rectangle {
+        Rectangle {
+            centre: self.point(&rectangle.centre),
+            ..*rectangle
+        }
+    }
+
+    fn style(&self, style: &Style) -> Style {
+        Style {
+            rotation: -style.rotation,
+            ..style.clone()
+        }
+    }
+
+    fn map(self, draw: impl FnOnce(R) -> R) -> Self {
+        FlippedRenderer {
+            inner: draw(self.inner),
+            ..self
+        }
+    }
+}
+
+impl<R> Renderer for FlippedRenderer<R>
+where
+    R: Renderer,
+{
+    fn configure(self, settings: &RendererSettings) -> Self {
+        self.map(|r| r.configure(settings))
+    }
+
+    fn setup(mut self, width: f64, height: f64) -> Self {
+        self.height = height;
+        self.map(|r| r.setup(width, height))
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let (from, to) = (self.point(from), self.point(to));
+        self.map(|r| r.draw_line(&from, &to, style))
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let points = points.iter().map(|p| self.point(p)).collect::<Vec<_>>();
+        self.map(|r| r.draw_polyline(&points, style))
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let centre = self.point(centre);
+        self.map(|r| r.draw_circle(radius, &centre, style))
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let (containment, style) = (self.rectangle(containment), self.style(style));
+        self.map(|r| r.draw_text(text, &containment, alignment, &style))
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_rectangle(&rectangle, style))
+    }
+
+    fn draw_text_with_rectangle(
+        self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let (rectangle, style) = (self.rectangle(rectangle), self.style(style));
+        self.map(|r| r.draw_text_with_rectangle(text, &rectangle, alignment, &style))
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_icon(icon, &rectangle, style))
+    }
+
+    fn finalize(self) -> Self {
+        self.map(|r| r.finalize())
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.inner.into_bytes()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        self.inner.render_into(buffer)
+    }
+}
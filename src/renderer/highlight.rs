@@ -0,0 +1,122 @@
+use crate::renderer::{Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2};
+use std::collections::HashSet;
+
+/// Opacity of the elements left out of the highlight.
+const FADED_OPACITY: f64 = 0.15;
+
+/// Fades every linked element drawn through it that isn't part of the
+/// highlighted set of ids. An element is part of it if its own id is, and
+/// so is one of the ids it links to, if it links any: a barrier's box on a
+/// connector is only kept for the component it sits on. Elements without
+/// links, such as the event, are drawn as they are.
+pub(crate) struct HighlightRenderer<'h, R> {
+    inner: R,
+    highlighted: &'h HashSet<String>,
+}
+
+impl<'h, R> HighlightRenderer<'h, R>
+where
+    R: Renderer,
+{
+    pub fn new(inner: R, highlighted: &'h HashSet<String>) -> Self {
+        HighlightRenderer { inner, highlighted }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn style(&self, style: &Style) -> Style {
+        let Some((id, links)) = style.links.split_first() else {
+            return style.clone();
+        };
+        let is_highlighted = self.highlighted.contains(id)
+            && (links.is_empty() || links.iter().any(|l| self.highlighted.contains(l)));
+        if is_highlighted {
+            return style.clone();
+        }
+        Style {
+            opacity: Some(FADED_OPACITY),
+            ..style.clone()
+        }
+    }
+
+    fn map(self, draw: impl FnOnce(R) -> R) -> Self {
+        HighlightRenderer {
+            inner: draw(self.inner),
+            ..self
+        }
+    }
+}
+
+impl<R> Renderer for HighlightRenderer<'_, R>
+where
+    R: Renderer,
+{
+    fn configure(self, settings: &RendererSettings) -> Self {
+        self.map(|r| r.configure(settings))
+    }
+
+    fn setup(self, width: f64, height: f64) -> Self {
+        self.map(|r| r.setup(width, height))
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_line(from, to, &style))
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_polyline(points, &style))
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_circle(radius, centre, &style))
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_text(text, containment, alignment, &style))
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_rectangle(rectangle, &style))
+    }
+
+    fn draw_text_with_rectangle(
+        self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_text_with_rectangle(text, rectangle, alignment, &style))
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let style = self.style(style);
+        self.map(|r| r.draw_icon(icon, rectangle, &style))
+    }
+
+    fn finalize(self) -> Self {
+        self.map(|r| r.finalize())
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.inner.into_bytes()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        self.inner.render_into(buffer)
+    }
+}
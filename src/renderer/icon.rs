@@ -0,0 +1,109 @@
+use crate::renderer::{Rectangle, Vector2};
+use std::f64::consts::TAU;
+
+/// Built-in symbol drawn in the corner of a component box.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Icon {
+    /// Human factors.
+    Person,
+    /// Equipment.
+    Gear,
+    /// Hazards.
+    Warning,
+    /// Electrical.
+    Bolt,
+}
+
+impl Icon {
+    /// Returns the icon with the given name, as written in the DSL.
+    pub fn from_name(name: &str) -> Option<Icon> {
+        match name {
+            "person" => Some(Icon::Person),
+            "gear" => Some(Icon::Gear),
+            "warning" => Some(Icon::Warning),
+            "bolt" => Some(Icon::Bolt),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Icon::Person => "person",
+            Icon::Gear => "gear",
+            Icon::Warning => "warning",
+            Icon::Bolt => "bolt",
+        }
+    }
+
+    /// Returns the closed rings outlining the icon, fitted into the
+    /// rectangle. Rings inside others are holes, so the outline is meant to
+    /// be filled with the even-odd rule.
+    pub(crate) fn outline(&self, rectangle: &Rectangle) -> Vec<Vec<Vector2>> {
+        let rings = match self {
+            Icon::Person => vec![
+                circle((0.5, 0.25), 0.2, 16),
+                vec![
+                    (0.15, 1.0),
+                    (0.15, 0.7),
+                    (0.3, 0.52),
+                    (0.7, 0.52),
+                    (0.85, 0.7),
+                    (0.85, 1.0),
+                ],
+            ],
+            Icon::Gear => {
+                // Teeth alternate between the outer and inner radius every
+                // two points.
+                let teeth = (0..32)
+                    .map(|i| {
+                        let radius = if (i / 2) % 2 == 0 { 0.5 } else { 0.38 };
+                        let angle = i as f64 * TAU / 32.0;
+                        (0.5 + radius * angle.cos(), 0.5 + radius * angle.sin())
+                    })
+                    .collect();
+                vec![teeth, circle((0.5, 0.5), 0.15, 12)]
+            }
+            Icon::Warning => vec![
+                vec![(0.5, 0.02), (0.98, 0.95), (0.02, 0.95)],
+                vec![(0.45, 0.35), (0.55, 0.35), (0.55, 0.7), (0.45, 0.7)],
+                vec![(0.45, 0.76), (0.55, 0.76), (0.55, 0.86), (0.45, 0.86)],
+            ],
+            Icon::Bolt => vec![vec![
+                (0.6, 0.0),
+                (0.15, 0.58),
+                (0.45, 0.58),
+                (0.35, 1.0),
+                (0.85, 0.4),
+                (0.55, 0.4),
+                (0.7, 0.0),
+            ]],
+        };
+        // Fit the unit square into the rectangle, keeping its aspect.
+        let size = rectangle.width.min(rectangle.height);
+        let left = rectangle.centre.x - size / 2.0;
+        let top = rectangle.centre.y - size / 2.0;
+        rings
+            .into_iter()
+            .map(|ring| {
+                ring.into_iter()
+                    .map(|(x, y)| Vector2 {
+                        x: left + x * size,
+                        y: top + y * size,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn circle(centre: (f64, f64), radius: f64, points: usize) -> Vec<(f64, f64)> {
+    (0..points)
+        .map(|i| {
+            let angle = i as f64 * TAU / points as f64;
+            (
+                centre.0 + radius * angle.cos(),
+                centre.1 + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,187 @@
+use crate::renderer::{
+    text_origin, Alignment, Color, Icon, Rectangle, Renderer, Style, Vector2, DEFAULT_FONT_SIZE,
+};
+use serde_json::{json, Map, Value};
+
+/// Records every draw call as a typed JSON object, so that a frontend can
+/// draw the diagram itself from the computed geometry.
+///
+/// The output is an array starting with a `canvas` object holding the
+/// diagram's size, followed by `rectangle`, `line`, `polyline`, `circle`,
+/// `text` and `icon` objects in drawing order. Coordinates have their
+/// origin at the top left.
+#[derive(Default)]
+pub struct JsonLayoutRenderer {
+    elements: Vec<Value>,
+}
+
+impl JsonLayoutRenderer {
+    pub fn new() -> Self {
+        JsonLayoutRenderer::default()
+    }
+
+    fn push(mut self, mut element: Value, style: &Style) -> Self {
+        if let Value::Object(fields) = &mut element {
+            fields.extend(style_fields(style));
+        }
+        self.elements.push(element);
+        self
+    }
+}
+
+impl Renderer for JsonLayoutRenderer {
+    fn setup(mut self, width: f64, height: f64) -> Self {
+        self.elements.push(json!({
+            "type": "canvas",
+            "width": width,
+            "height": height,
+        }));
+        self
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let element = json!({
+            "type": "line",
+            "x1": from.x,
+            "y1": from.y,
+            "x2": to.x,
+            "y2": to.y,
+        });
+        self.push(element, style)
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let points = points.iter().map(|p| json!([p.x, p.y])).collect::<Vec<_>>();
+        let element = json!({
+            "type": "polyline",
+            "points": points,
+        });
+        self.push(element, style)
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let element = json!({
+            "type": "circle",
+            "cx": centre.x,
+            "cy": centre.y,
+            "r": radius,
+        });
+        self.push(element, style)
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+        let origin = text_origin(text, font_size, containment, alignment);
+        let element = json!({
+            "type": "text",
+            "text": text,
+            "x": origin.x,
+            "y": origin.y,
+            "font_size": font_size,
+            "rotation": style.rotation,
+            "rotation_centre": {
+                "x": containment.centre.x,
+                "y": containment.centre.y,
+            },
+        });
+        self.push(element, style)
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let element = json!({
+            "type": "rectangle",
+            "x": rectangle.centre.x - rectangle.width / 2.0,
+            "y": rectangle.centre.y - rectangle.height / 2.0,
+            "w": rectangle.width,
+            "h": rectangle.height,
+        });
+        self.push(element, style)
+    }
+
+    fn draw_text_with_rectangle(
+        mut self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let text_style = Style {
+            fill: style.fill.map(|fill| fill.contrasting_text()),
+            font_size: style.font_size,
+            class: style.class,
+            opacity: style.opacity,
+            ..Style::default()
+        };
+        self = self.draw_rectangle(&rectangle.with_padding(2.0), style);
+        self.draw_text(text, rectangle, alignment, &text_style)
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let element = json!({
+            "type": "icon",
+            "icon": icon.name(),
+            "x": rectangle.centre.x - rectangle.width / 2.0,
+            "y": rectangle.centre.y - rectangle.height / 2.0,
+            "w": rectangle.width,
+            "h": rectangle.height,
+        });
+        self.push(element, style)
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        serde_json::to_vec(&self.elements).unwrap()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        serde_json::to_writer(buffer, &self.elements).unwrap();
+    }
+}
+
+/// Returns the style attributes of an element, leaving out unset ones.
+fn style_fields(style: &Style) -> Map<String, Value> {
+    let mut fields = Map::new();
+    let color = |color: Color| Value::from(color.to_svg_string());
+    if let Some(fill) = style.fill {
+        fields.insert("fill".to_owned(), color(fill));
+    }
+    if let Some(stroke) = style.stroke {
+        fields.insert("stroke".to_owned(), color(stroke));
+    }
+    if let Some(stroke_width) = style.stroke_width {
+        fields.insert("stroke_width".to_owned(), Value::from(stroke_width));
+    }
+    if style.dashed {
+        fields.insert("dashed".to_owned(), Value::from(true));
+    }
+    if style.italic {
+        fields.insert("italic".to_owned(), Value::from(true));
+    }
+    if style.bold {
+        fields.insert("bold".to_owned(), Value::from(true));
+    }
+    if let Some(class) = style.class {
+        fields.insert("class".to_owned(), Value::from(class));
+    }
+    if let Some(tooltip) = &style.tooltip {
+        fields.insert("tooltip".to_owned(), Value::from(tooltip.as_str()));
+    }
+    if let Some(opacity) = style.opacity {
+        fields.insert("opacity".to_owned(), Value::from(opacity));
+    }
+    if let Some(line_cap) = style.line_cap {
+        fields.insert("line_cap".to_owned(), Value::from(line_cap.to_svg_string()));
+    }
+    if let Some(line_join) = style.line_join {
+        fields.insert(
+            "line_join".to_owned(),
+            Value::from(line_join.to_svg_string()),
+        );
+    }
+    fields
+}
@@ -1,19 +1,151 @@
+mod bounds;
+mod flipped;
+mod highlight;
+mod icon;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod raw_svg;
+mod scaled;
+#[cfg(feature = "svg")]
 mod svg;
-pub use svg::SvgRenderer;
+mod svg_common;
+mod tee;
+mod tile;
+pub use crate::brush::fit_text;
+pub(crate) use bounds::BoundsRenderer;
+pub use flipped::FlippedRenderer;
+pub(crate) use highlight::HighlightRenderer;
+pub use icon::Icon;
+#[cfg(feature = "json")]
+pub use json::JsonLayoutRenderer;
+#[cfg(feature = "pdf")]
+pub use pdf::PdfRenderer;
+pub use raw_svg::RawSvgRenderer;
+pub(crate) use scaled::ScaledRenderer;
+#[cfg(feature = "svg")]
+pub use svg::{SvgRenderer, SvgRendererBuilder};
+pub use svg_common::extract_svg_source;
+pub use tee::TeeRenderer;
+pub(crate) use tile::TileRenderer;
 
-#[derive(Clone, Copy, Debug)]
+use std::fmt;
+use std::str::FromStr;
+
+/// Ratio between the font size and the width of a monospace character.
+const FONT_WIDTH: f64 = 1.7;
+const DEFAULT_FONT_SIZE: f64 = 18.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vector2 {
     pub x: f64,
     pub y: f64,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct Rectangle {
     pub centre: Vector2,
     pub width: f64,
     pub height: f64,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Error returned when parsing a malformed or unknown colour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorParseError(String);
+
+/// CSS colour names accepted by [`Color::parse`].
+const NAMED_COLORS: [(&str, Color); 24] = [
+    ("black", Color::rgb(0x00, 0x00, 0x00)),
+    ("silver", Color::rgb(0xc0, 0xc0, 0xc0)),
+    ("gray", Color::rgb(0x80, 0x80, 0x80)),
+    ("grey", Color::rgb(0x80, 0x80, 0x80)),
+    ("white", Color::rgb(0xff, 0xff, 0xff)),
+    ("maroon", Color::rgb(0x80, 0x00, 0x00)),
+    ("red", Color::rgb(0xff, 0x00, 0x00)),
+    ("purple", Color::rgb(0x80, 0x00, 0x80)),
+    ("fuchsia", Color::rgb(0xff, 0x00, 0xff)),
+    ("green", Color::rgb(0x00, 0x80, 0x00)),
+    ("lime", Color::rgb(0x00, 0xff, 0x00)),
+    ("olive", Color::rgb(0x80, 0x80, 0x00)),
+    ("yellow", Color::rgb(0xff, 0xff, 0x00)),
+    ("navy", Color::rgb(0x00, 0x00, 0x80)),
+    ("blue", Color::rgb(0x00, 0x00, 0xff)),
+    ("teal", Color::rgb(0x00, 0x80, 0x80)),
+    ("aqua", Color::rgb(0x00, 0xff, 0xff)),
+    ("orange", Color::rgb(0xff, 0xa5, 0x00)),
+    ("gold", Color::rgb(0xff, 0xd7, 0x00)),
+    ("pink", Color::rgb(0xff, 0xc0, 0xcb)),
+    ("brown", Color::rgb(0xa5, 0x2a, 0x2a)),
+    ("lightgray", Color::rgb(0xd3, 0xd3, 0xd3)),
+    ("darkgray", Color::rgb(0xa9, 0xa9, 0xa9)),
+    ("darkgreen", Color::rgb(0x00, 0x64, 0x00)),
+];
+
+/// Optional styling applied on top of a renderer's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    /// Width of the outline, overriding the renderer's own.
+    pub stroke_width: Option<f64>,
+    pub dashed: bool,
+    pub font_size: Option<f64>,
+    pub italic: bool,
+    pub bold: bool,
+    /// Semantic class of the element, e.g. `component` or `barrier`.
+    pub class: Option<&'static str>,
+    pub line_cap: Option<LineCap>,
+    pub line_join: Option<LineJoin>,
+    /// Text shown when hovering the element, where supported.
+    pub tooltip: Option<String>,
+    /// Ids of the diagram elements the element is drawn for, its own first,
+    /// e.g. a connector lists its component and the barriers on it.
+    /// Interactive outputs highlight the elements sharing an id.
+    pub links: Vec<String>,
+    /// Opacity of the element, from 0 (invisible) to 1 (opaque, as when
+    /// unset).
+    pub opacity: Option<f64>,
+    /// Clockwise rotation of text, in degrees, about the centre of its
+    /// containment.
+    pub rotation: f64,
+}
+
+/// Settings of a renderer that an input can choose in its front matter,
+/// overriding those the renderer was built with. Unset ones are left alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RendererSettings {
+    /// Font family of all text, as set with [`RawSvgRenderer::with_font`].
+    pub font_family: Option<String>,
+    /// Width of the strokes not given one by their style, in pixels.
+    pub stroke_width: Option<u32>,
+}
+
+/// Shape drawn at the ends of open lines.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+/// Shape drawn where two segments of an outline meet.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
 #[derive(Copy, Clone)]
 pub enum Alignment {
     Center,
@@ -22,18 +154,56 @@ pub enum Alignment {
 }
 
 pub trait Renderer {
+    /// Applies the settings chosen by the options, before
+    /// [`Renderer::setup`]. Renderers without such settings ignore them, as
+    /// they do by default.
+    fn configure(self, _settings: &RendererSettings) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
     fn setup(self, width: f64, height: f64) -> Self;
-    fn draw_line(self, from: &Vector2, to: &Vector2) -> Self;
-    fn draw_circle(self, radius: f64, centre: &Vector2) -> Self;
-    fn draw_text(self, text: &str, containment: &Rectangle, alignment: Alignment) -> Self;
-    fn draw_rectangle(self, rectangle: &Rectangle) -> Self;
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self;
+    /// Draws an open line through each of the points in turn.
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self;
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self;
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self;
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self;
     fn draw_text_with_rectangle(
         self,
         text: &str,
         rectangle: &Rectangle,
         alignment: Alignment,
+        style: &Style,
     ) -> Self;
+    /// Draws a built-in icon, fitted into the rectangle.
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self;
+    /// Finishes building the output once the diagram is drawn, before it
+    /// is serialized by [`Renderer::into_bytes`], e.g. closing open
+    /// elements. Called at the end of each render; does nothing by default.
+    fn finalize(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
     fn into_bytes(self) -> Vec<u8>;
+    /// Appends the output to `buffer`, so that a buffer can be reused
+    /// across renders. By default, the output of [`Renderer::into_bytes`]
+    /// is copied in; renderers that can write in place do so instead.
+    fn render_into(self, buffer: &mut Vec<u8>)
+    where
+        Self: Sized,
+    {
+        buffer.extend_from_slice(&self.into_bytes());
+    }
 }
 
 impl Rectangle {
@@ -43,3 +213,153 @@ impl Rectangle {
         self
     }
 }
+
+impl Style {
+    pub fn with_class(class: &'static str) -> Self {
+        Style {
+            class: Some(class),
+            ..Style::default()
+        }
+    }
+}
+
+impl LineCap {
+    pub fn to_svg_string(&self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+}
+
+impl LineJoin {
+    pub fn to_svg_string(&self) -> &'static str {
+        match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        }
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(0xff, 0xff, 0xff);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+
+    /// Relative luminance as defined by WCAG, from 0 (black) to 1 (white).
+    pub fn luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = channel as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Picks black or white, whichever contrasts more with this colour
+    /// when used as a background.
+    pub fn contrasting_text(&self) -> Color {
+        // Luminance at which the contrast ratio against black and white is equal.
+        if self.luminance() > 0.179 {
+            Color::BLACK
+        } else {
+            Color::WHITE
+        }
+    }
+
+    /// Parses a colour written as `#rgb`, `#rrggbb`, `rgb(r, g, b)` or a
+    /// CSS colour name, ignoring case. Colours given in front matter, such
+    /// as `spine_color`, are read through here, so that all report errors
+    /// alike.
+    pub fn parse(value: &str) -> Result<Color, ColorParseError> {
+        let error = || ColorParseError(value.to_owned());
+        let normalized = value.trim().to_lowercase();
+        if let Some(hex) = normalized.strip_prefix('#') {
+            return parse_hex_color(hex).ok_or_else(error);
+        }
+        if let Some(channels) = normalized
+            .strip_prefix("rgb(")
+            .and_then(|v| v.strip_suffix(')'))
+        {
+            let channels = channels
+                .split(',')
+                .map(|c| c.trim().parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error())?;
+            let [r, g, b] = channels[..] else {
+                return Err(error());
+            };
+            return Ok(Color::rgb(r, g, b));
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == normalized)
+            .map(|(_, color)| *color)
+            .ok_or_else(error)
+    }
+
+    /// Formats as `#rrggbb`, which [`Color::parse`] reads back.
+    pub fn to_svg_string(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::parse(s)
+    }
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid colour: {}", self.0)
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+/// Parses the digits of a `#rgb` or `#rrggbb` colour.
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, length: usize| u8::from_str_radix(&hex[i..i + length], 16).ok();
+    match hex.len() {
+        // Each digit is repeated, so that `#f80` is `#ff8800`.
+        3 => Some(Color::rgb(
+            channel(0, 1)? * 0x11,
+            channel(1, 1)? * 0x11,
+            channel(2, 1)? * 0x11,
+        )),
+        6 => Some(Color::rgb(channel(0, 2)?, channel(2, 2)?, channel(4, 2)?)),
+        _ => None,
+    }
+}
+
+/// Computes the baseline start of a single line of monospace text placed
+/// within `containment`, so that all renderers position text alike.
+pub(crate) fn text_origin(
+    text: &str,
+    font_size: f64,
+    containment: &Rectangle,
+    alignment: Alignment,
+) -> Vector2 {
+    let width = (text.len() as f64) * font_size / FONT_WIDTH;
+    let y = containment.centre.y + (font_size / (FONT_WIDTH * 2.0));
+    let x = match alignment {
+        Alignment::Center => containment.centre.x - (width / 2.0),
+        Alignment::Left => containment.centre.x - (containment.width / 2.0),
+        Alignment::Right => containment.centre.x + (containment.width / 2.0) - width,
+    };
+    Vector2 { x, y }
+}
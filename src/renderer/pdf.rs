@@ -0,0 +1,279 @@
+use crate::renderer::{
+    text_origin, Alignment, Color, Icon, LineCap, LineJoin, Rectangle, Renderer, RendererSettings,
+    Style, Vector2, DEFAULT_FONT_SIZE,
+};
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::utils::calculate_points_for_circle;
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Line, LineCapStyle, LineDashPattern, LineJoinStyle, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Polygon, Pt, Rgb, TextMatrix,
+};
+
+const DASH_LENGTH: i64 = 10;
+const DASH_GAP: i64 = 6;
+
+/// Renders diagrams into a PDF document, one page per diagram.
+///
+/// Each call to `setup` starts a new page sized to the diagram, so a
+/// multi-page document is built by threading the same renderer through
+/// several calls to [`crate::render_bowtie`] before calling `into_bytes`.
+pub struct PdfRenderer {
+    document: PdfDocumentReference,
+    font: IndirectFontRef,
+    italic_font: IndirectFontRef,
+    bold_font: IndirectFontRef,
+    bold_italic_font: IndirectFontRef,
+    layer: Option<PdfLayerReference>,
+    page_height: f64,
+    stroke_width: f64,
+}
+
+impl PdfRenderer {
+    pub fn new(title: &str) -> Self {
+        let document = PdfDocument::empty(title);
+        let font = document.add_builtin_font(BuiltinFont::Courier).unwrap();
+        let italic_font = document
+            .add_builtin_font(BuiltinFont::CourierOblique)
+            .unwrap();
+        let bold_font = document.add_builtin_font(BuiltinFont::CourierBold).unwrap();
+        let bold_italic_font = document
+            .add_builtin_font(BuiltinFont::CourierBoldOblique)
+            .unwrap();
+        PdfRenderer {
+            document,
+            font,
+            italic_font,
+            bold_font,
+            bold_italic_font,
+            layer: None,
+            page_height: 0.0,
+            stroke_width: 3.0,
+        }
+    }
+
+    fn layer(&self) -> &PdfLayerReference {
+        self.layer
+            .as_ref()
+            .expect("setup must be called before drawing")
+    }
+
+    /// Converts from the top-left origin used by the layout to PDF's
+    /// bottom-left origin.
+    fn point(&self, v: &Vector2) -> Point {
+        Point {
+            x: Pt(v.x as f32),
+            y: Pt((self.page_height - v.y) as f32),
+        }
+    }
+
+    fn apply_style(&self, style: &Style, default_fill: Color) {
+        let layer = self.layer();
+        layer.set_fill_color(pdf_color(style.fill.unwrap_or(default_fill), style));
+        layer.set_outline_color(pdf_color(style.stroke.unwrap_or(Color::BLACK), style));
+        let stroke_width = style.stroke_width.unwrap_or(self.stroke_width);
+        layer.set_outline_thickness(stroke_width as f32);
+        let dash_pattern = if style.dashed {
+            LineDashPattern {
+                dash_1: Some(DASH_LENGTH),
+                gap_1: Some(DASH_GAP),
+                ..LineDashPattern::default()
+            }
+        } else {
+            LineDashPattern::default()
+        };
+        layer.set_line_dash_pattern(dash_pattern);
+        // Line shapes persist on the layer, so unset ones are reset.
+        layer.set_line_cap_style(match style.line_cap.unwrap_or_default() {
+            LineCap::Butt => LineCapStyle::Butt,
+            LineCap::Round => LineCapStyle::Round,
+            LineCap::Square => LineCapStyle::ProjectingSquare,
+        });
+        layer.set_line_join_style(match style.line_join.unwrap_or_default() {
+            LineJoin::Miter => LineJoinStyle::Miter,
+            LineJoin::Round => LineJoinStyle::Round,
+            // printpdf names the bevel join `Limit`.
+            LineJoin::Bevel => LineJoinStyle::Limit,
+        });
+    }
+
+    fn draw_polygon(&self, points: Vec<(Point, bool)>, style: &Style) {
+        self.apply_style(style, Color::WHITE);
+        self.layer().add_polygon(Polygon {
+            rings: vec![points],
+            mode: PaintMode::FillStroke,
+            winding_order: WindingOrder::NonZero,
+        });
+    }
+}
+
+impl Renderer for PdfRenderer {
+    /// Applies the stroke width. The font family is ignored, as only the
+    /// built-in Courier fonts are embedded.
+    fn configure(mut self, settings: &RendererSettings) -> Self {
+        if let Some(width) = settings.stroke_width {
+            self.stroke_width = width as f64;
+        }
+        self
+    }
+
+    fn setup(mut self, width: f64, height: f64) -> Self {
+        let (page, layer) = self.document.add_page(
+            Mm::from(Pt(width as f32)),
+            Mm::from(Pt(height as f32)),
+            "diagram",
+        );
+        self.layer = Some(self.document.get_page(page).get_layer(layer));
+        self.page_height = height;
+        self
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        self.apply_style(style, Color::WHITE);
+        self.layer().add_line(Line {
+            points: vec![(self.point(from), false), (self.point(to), false)],
+            is_closed: false,
+        });
+        self
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        self.apply_style(style, Color::WHITE);
+        self.layer().add_line(Line {
+            points: points.iter().map(|p| (self.point(p), false)).collect(),
+            is_closed: false,
+        });
+        self
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let centre = self.point(centre);
+        let points = calculate_points_for_circle(Pt(radius as f32), centre.x, centre.y);
+        self.draw_polygon(points, style);
+        self
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+        let origin = text_origin(text, font_size, containment, alignment);
+        let font = match (style.italic, style.bold) {
+            (false, false) => &self.font,
+            (true, false) => &self.italic_font,
+            (false, true) => &self.bold_font,
+            (true, true) => &self.bold_italic_font,
+        };
+        let layer = self.layer();
+        layer.set_fill_color(pdf_color(style.fill.unwrap_or(Color::BLACK), style));
+        if style.rotation == 0.0 {
+            let origin = self.point(&origin);
+            layer.use_text(
+                text,
+                font_size as f32,
+                Mm::from(origin.x),
+                Mm::from(origin.y),
+                font,
+            );
+            return self;
+        }
+        // Rotate the origin about the containment's centre, as SVG does,
+        // then rotate the text itself. PDF angles are counter-clockwise.
+        let centre = containment.centre;
+        let (sin, cos) = style.rotation.to_radians().sin_cos();
+        let dx = origin.x - centre.x;
+        let dy = origin.y - centre.y;
+        let origin = self.point(&Vector2 {
+            x: centre.x + dx * cos - dy * sin,
+            y: centre.y + dx * sin + dy * cos,
+        });
+        layer.begin_text_section();
+        layer.set_font(font, font_size as f32);
+        layer.set_text_matrix(TextMatrix::TranslateRotate(
+            origin.x,
+            origin.y,
+            -style.rotation as f32,
+        ));
+        layer.write_text(text, font);
+        layer.end_text_section();
+        self
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let half_width = rectangle.width / 2.0;
+        let half_height = rectangle.height / 2.0;
+        let centre = rectangle.centre;
+        let corners = [
+            (-half_width, -half_height),
+            (half_width, -half_height),
+            (half_width, half_height),
+            (-half_width, half_height),
+        ];
+        let points = corners
+            .iter()
+            .map(|(dx, dy)| {
+                let corner = Vector2 {
+                    x: centre.x + dx,
+                    y: centre.y + dy,
+                };
+                (self.point(&corner), false)
+            })
+            .collect();
+        self.draw_polygon(points, style);
+        self
+    }
+
+    fn draw_text_with_rectangle(
+        mut self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let text_style = Style {
+            fill: style.fill.map(|fill| fill.contrasting_text()),
+            font_size: style.font_size,
+            class: style.class,
+            opacity: style.opacity,
+            ..Style::default()
+        };
+        self = self.draw_rectangle(&rectangle.with_padding(2.0), style);
+        self = self.draw_text(text, rectangle, alignment, &text_style);
+        self
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        self.apply_style(style, Color::BLACK);
+        let rings = icon
+            .outline(rectangle)
+            .iter()
+            .map(|ring| ring.iter().map(|p| (self.point(p), false)).collect())
+            .collect();
+        self.layer().add_polygon(Polygon {
+            rings,
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::EvenOdd,
+        });
+        self
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.document.save_to_bytes().unwrap()
+    }
+}
+
+/// Converts a colour, faded by the style's opacity. Transparency isn't
+/// used, so colours are instead mixed with the white page.
+fn pdf_color(color: Color, style: &Style) -> printpdf::Color {
+    let opacity = style.opacity.unwrap_or(1.0).clamp(0.0, 1.0) as f32;
+    let channel = |c: u8| 1.0 - (1.0 - c as f32 / 255.0) * opacity;
+    printpdf::Color::Rgb(Rgb::new(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        None,
+    ))
+}
@@ -0,0 +1,331 @@
+use crate::renderer::svg_common::{
+    color_or, wrap_cdata, DASH_ARRAY, DEFAULT_BG_FILL, DEFAULT_STROKE, DEFAULT_TEXT_FILL,
+    FONT_FAMILY, INTERACTIVE_SCRIPT, INTERACTIVE_STYLE, SOURCE_METADATA_END, SOURCE_METADATA_START,
+};
+use crate::renderer::{
+    text_origin, Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2,
+    DEFAULT_FONT_SIZE,
+};
+use std::fmt::{self, Display, Write};
+
+/// Renders SVG by writing the markup directly, without the `svg` crate.
+///
+/// The output matches that of [`crate::renderer::SvgRenderer`] closely, but
+/// isn't guaranteed to be byte for byte identical.
+#[derive(Clone)]
+pub struct RawSvgRenderer {
+    size: (f64, f64),
+    body: String,
+    stroke_width: u32,
+    source: Option<String>,
+    font_family: String,
+    /// Size text is drawn at, scaling the sizes set by the layout.
+    font_size: f64,
+    interactive: bool,
+}
+
+/// An element being built, written once complete.
+struct Element {
+    name: &'static str,
+    attributes: Vec<(&'static str, String)>,
+    /// Escaped text content.
+    text: Option<String>,
+    title: Option<String>,
+}
+
+impl RawSvgRenderer {
+    pub fn new() -> Self {
+        RawSvgRenderer {
+            size: (0.0, 0.0),
+            body: String::new(),
+            stroke_width: 3,
+            source: None,
+            font_family: FONT_FAMILY.to_owned(),
+            font_size: DEFAULT_FONT_SIZE,
+            interactive: false,
+        }
+    }
+
+    /// See [`crate::renderer::SvgRenderer::with_font`].
+    pub fn with_font(mut self, family: &str, size: f64) -> Self {
+        self.font_family = family.to_owned();
+        self.font_size = size;
+        self
+    }
+
+    /// See [`crate::renderer::SvgRenderer::with_source`].
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// See [`crate::renderer::SvgRenderer::interactive`].
+    pub fn interactive(mut self) -> Self {
+        self.interactive = true;
+        self
+    }
+
+    fn stroke_width(&self, style: &Style) -> f64 {
+        style.stroke_width.unwrap_or(self.stroke_width as f64)
+    }
+
+    /// Applies the attributes all elements share, and adds the element.
+    fn add(mut self, mut element: Element, style: &Style) -> Self {
+        if let Some(class) = style.class {
+            element.set("class", class);
+        }
+        if self.interactive && !style.links.is_empty() {
+            element.set("data-links", style.links.join(" "));
+        }
+        if let Some(opacity) = style.opacity {
+            element.set("opacity", opacity);
+        }
+        element.title = style.tooltip.as_deref().map(escape);
+        // Writing to a string can't fail.
+        let _ = writeln!(self.body, "{element}");
+        self
+    }
+
+    fn path(&self, data: String, style: &Style) -> Element {
+        let mut path = Element::new("path");
+        path.set("fill", "none");
+        path.set("stroke", color_or(style.stroke, DEFAULT_STROKE));
+        path.set("stroke-width", self.stroke_width(style));
+        path.set("d", data);
+        set_dashes(&mut path, style);
+        set_line_shape(&mut path, style);
+        path
+    }
+}
+
+impl Default for RawSvgRenderer {
+    fn default() -> Self {
+        RawSvgRenderer::new()
+    }
+}
+
+impl Element {
+    fn new(name: &'static str) -> Self {
+        Element {
+            name,
+            attributes: Vec::new(),
+            text: None,
+            title: None,
+        }
+    }
+
+    fn set(&mut self, name: &'static str, value: impl Display) {
+        let value = escape(&value.to_string()).replace('"', "&quot;");
+        match self.attributes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.attributes.push((name, value)),
+        }
+    }
+}
+
+/// Writes attributes in alphabetical order, as the `svg` crate does.
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}", self.name)?;
+        let mut attributes = self.attributes.iter().collect::<Vec<_>>();
+        attributes.sort_by_key(|(name, _)| *name);
+        for (name, value) in attributes {
+            write!(f, " {name}=\"{value}\"")?;
+        }
+        if self.text.is_none() && self.title.is_none() {
+            return f.write_str("/>");
+        }
+        f.write_str(">\n")?;
+        if let Some(text) = &self.text {
+            writeln!(f, "{text}")?;
+        }
+        if let Some(title) = &self.title {
+            writeln!(f, "<title>{title}</title>")?;
+        }
+        write!(f, "</{}>", self.name)
+    }
+}
+
+impl Renderer for RawSvgRenderer {
+    fn configure(mut self, settings: &RendererSettings) -> Self {
+        if let Some(family) = &settings.font_family {
+            self.font_family = family.clone();
+        }
+        if let Some(width) = settings.stroke_width {
+            self.stroke_width = width;
+        }
+        self
+    }
+
+    fn setup(mut self, width: f64, height: f64) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        self.draw_polyline(&[*from, *to], style)
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let Some(data) = polyline_data(points, false) else {
+            return self;
+        };
+        let path = self.path(data, style);
+        self.add(path, style)
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let mut circle = Element::new("circle");
+        circle.set("cx", centre.x);
+        circle.set("cy", centre.y);
+        circle.set("r", radius);
+        circle.set("stroke", color_or(style.stroke, DEFAULT_STROKE));
+        circle.set("stroke-width", self.stroke_width(style));
+        circle.set("fill", color_or(style.fill, DEFAULT_BG_FILL));
+        set_dashes(&mut circle, style);
+        self.add(circle, style)
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let font_size = match style.font_size {
+            Some(size) => size * (self.font_size / DEFAULT_FONT_SIZE),
+            None => self.font_size,
+        };
+        let origin = text_origin(text, font_size, containment, alignment);
+        let mut element = Element::new("text");
+        element.set("x", origin.x);
+        element.set("y", origin.y);
+        element.set("font-size", font_size);
+        element.set("fill", color_or(style.fill, DEFAULT_TEXT_FILL));
+        element.set("font-family", &self.font_family);
+        if style.italic {
+            element.set("font-style", "italic");
+        }
+        if style.bold {
+            element.set("font-weight", "bold");
+        }
+        if style.rotation != 0.0 {
+            let centre = containment.centre;
+            let transform = format!("rotate({} {} {})", style.rotation, centre.x, centre.y);
+            element.set("transform", transform);
+        }
+        element.text = Some(escape(text));
+        self.add(element, style)
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let left = rectangle.centre.x - (rectangle.width / 2.0);
+        let top = rectangle.centre.y - (rectangle.height / 2.0);
+        let (width, height) = (rectangle.width as f32, rectangle.height as f32);
+        let data = format!(
+            "M{},{} l{width},0 l0,{height} l{},0 z",
+            left as f32, top as f32, -width
+        );
+        let mut path = self.path(data, style);
+        path.set("fill", color_or(style.fill, DEFAULT_BG_FILL));
+        path.set("font-family", &self.font_family);
+        self.add(path, style)
+    }
+
+    fn draw_text_with_rectangle(
+        mut self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let text_style = Style {
+            fill: style.fill.map(|fill| fill.contrasting_text()),
+            font_size: style.font_size,
+            class: style.class,
+            tooltip: style.tooltip.clone(),
+            links: style.links.clone(),
+            opacity: style.opacity,
+            ..Style::default()
+        };
+        self = self.draw_rectangle(&rectangle.with_padding(2.0), style);
+        self.draw_text(text, rectangle, alignment, &text_style)
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let data = icon
+            .outline(rectangle)
+            .iter()
+            .filter_map(|ring| polyline_data(ring, true))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut path = Element::new("path");
+        path.set("fill", color_or(style.fill, DEFAULT_TEXT_FILL));
+        path.set("fill-rule", "evenodd");
+        path.set("stroke", "none");
+        path.set("d", data);
+        self.add(path, style)
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let (width, height) = self.size;
+        let mut svg = format!(
+            "<svg viewBox=\"0 0 {width} {height}\" xmlns=\"http://www.w3.org/2000/svg\">\n"
+        );
+        if let Some(source) = &self.source {
+            let cdata = wrap_cdata(source);
+            svg.push_str(&format!(
+                "{SOURCE_METADATA_START}{cdata}{SOURCE_METADATA_END}\n"
+            ));
+        }
+        svg.push_str(&self.body);
+        if self.interactive {
+            svg.push_str(&format!(
+                "<style>\n{}\n</style>\n",
+                escape(INTERACTIVE_STYLE)
+            ));
+            svg.push_str(&format!(
+                "<script>\n{}\n</script>\n",
+                escape(INTERACTIVE_SCRIPT)
+            ));
+        }
+        svg.push_str("</svg>");
+        svg.into_bytes()
+    }
+}
+
+/// Formats path data through the points, closing the path if asked to.
+/// Coordinates are written in single precision, as the `svg` crate does.
+fn polyline_data(points: &[Vector2], closed: bool) -> Option<String> {
+    let (start, rest) = points.split_first()?;
+    let mut data = format!("M{},{}", start.x as f32, start.y as f32);
+    for point in rest {
+        let _ = write!(data, " L{},{}", point.x as f32, point.y as f32);
+    }
+    if closed {
+        data.push_str(" z");
+    }
+    Some(data)
+}
+
+fn set_dashes(element: &mut Element, style: &Style) {
+    if style.dashed {
+        element.set("stroke-dasharray", DASH_ARRAY);
+    }
+}
+
+fn set_line_shape(element: &mut Element, style: &Style) {
+    if let Some(line_cap) = style.line_cap {
+        element.set("stroke-linecap", line_cap.to_svg_string());
+    }
+    if let Some(line_join) = style.line_join {
+        element.set("stroke-linejoin", line_join.to_svg_string());
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,130 @@
+use crate::renderer::{
+    Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2, DEFAULT_FONT_SIZE,
+};
+
+/// Scales and then offsets everything drawn through it, to fit a layout into
+/// a canvas of a different size. Stroke widths are left as they are.
+pub(crate) struct ScaledRenderer<R> {
+    inner: R,
+    scale: f64,
+    offset: Vector2,
+}
+
+impl<R> ScaledRenderer<R>
+where
+    R: Renderer,
+{
+    pub fn new(inner: R, scale: f64, offset: Vector2) -> Self {
+        ScaledRenderer {
+            inner,
+            scale,
+            offset,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn point(&self, v: &Vector2) -> Vector2 {
+        Vector2 {
+            x: v.x * self.scale + self.offset.x,
+            y: v.y * self.scale + self.offset.y,
+        }
+    }
+
+    fn rectangle(&self, rectangle: &Rectangle) -> Rectangle {
+        Rectangle {
+            centre: self.point(&rectangle.centre),
+            width: rectangle.width * self.scale,
+            height: rectangle.height * self.scale,
+        }
+    }
+
+    fn style(&self, style: &Style) -> Style {
+        Style {
+            font_size: Some(style.font_size.unwrap_or(DEFAULT_FONT_SIZE) * self.scale),
+            ..style.clone()
+        }
+    }
+
+    fn map(self, draw: impl FnOnce(R) -> R) -> Self {
+        ScaledRenderer {
+            inner: draw(self.inner),
+            ..self
+        }
+    }
+}
+
+impl<R> Renderer for ScaledRenderer<R>
+where
+    R: Renderer,
+{
+    fn configure(self, settings: &RendererSettings) -> Self {
+        self.map(|r| r.configure(settings))
+    }
+
+    fn setup(self, width: f64, height: f64) -> Self {
+        let (width, height) = (width * self.scale, height * self.scale);
+        self.map(|r| r.setup(width, height))
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let (from, to) = (self.point(from), self.point(to));
+        self.map(|r| r.draw_line(&from, &to, style))
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let points = points.iter().map(|p| self.point(p)).collect::<Vec<_>>();
+        self.map(|r| r.draw_polyline(&points, style))
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let (radius, centre) = (radius * self.scale, self.point(centre));
+        self.map(|r| r.draw_circle(radius, &centre, style))
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let (containment, style) = (self.rectangle(containment), self.style(style));
+        self.map(|r| r.draw_text(text, &containment, alignment, &style))
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_rectangle(&rectangle, style))
+    }
+
+    fn draw_text_with_rectangle(
+        self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let (rectangle, style) = (self.rectangle(rectangle), self.style(style));
+        self.map(|r| r.draw_text_with_rectangle(text, &rectangle, alignment, &style))
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_icon(icon, &rectangle, style))
+    }
+
+    fn finalize(self) -> Self {
+        self.map(|r| r.finalize())
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.inner.into_bytes()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        self.inner.render_into(buffer)
+    }
+}
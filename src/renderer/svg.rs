@@ -1,15 +1,98 @@
-use crate::renderer::{Alignment, Rectangle, Renderer, Vector2};
+use crate::renderer::{
+    text_origin, Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2,
+    DEFAULT_FONT_SIZE,
+};
 use svg::node::element::path::Data;
-use svg::node::element::{Circle, Path, Text};
-use svg::Document;
+use svg::node::element::{Animate, Circle, Group, Path, Script, Text, Title};
+use svg::{Document, Node};
 
-const FONT_WIDTH: f64 = 1.7;
-const FONT_FAMILY: &str = "Courier, monospace";
-const DEFAULT_BG_FILL: &str = "white";
+use crate::renderer::svg_common::{
+    color_or, wrap_cdata, DASH_ARRAY, DEFAULT_BG_FILL, DEFAULT_STROKE, DEFAULT_TEXT_FILL,
+    FONT_FAMILY, INTERACTIVE_SCRIPT, INTERACTIVE_STYLE, SOURCE_METADATA_END, SOURCE_METADATA_START,
+};
 
+#[derive(Clone)]
 pub struct SvgRenderer {
     document: Document,
     stroke_width: u32,
+    source: Option<String>,
+    font_family: String,
+    /// Size text is drawn at, scaling the sizes set by the layout.
+    font_size: f64,
+    interactive: bool,
+    /// Holds the drawn elements in minimal mode, carrying the attributes
+    /// they share.
+    group: Option<Group>,
+    /// Decimal places coordinates are rounded to, if any.
+    precision: Option<u32>,
+    /// Groups of the elements drawn for each barrier, in the order they
+    /// were first drawn, in presentation mode.
+    barrier_groups: Option<Vec<(String, Group)>>,
+}
+
+/// Seconds each barrier stays in focus in presentation mode.
+const FOCUS_STEP: f64 = 2.0;
+/// Opacity of the barriers out of focus in presentation mode.
+const UNFOCUSED_OPACITY: f64 = 0.2;
+
+/// Configures an [`SvgRenderer`] step by step. Unset options keep the
+/// defaults of [`SvgRenderer::new`].
+pub struct SvgRendererBuilder {
+    renderer: SvgRenderer,
+}
+
+impl SvgRendererBuilder {
+    /// Width of every stroke, in pixels.
+    pub fn stroke_width(mut self, width: u32) -> Self {
+        self.renderer.stroke_width = width;
+        self
+    }
+
+    /// See [`SvgRenderer::with_font`].
+    pub fn font(mut self, family: &str, size: f64) -> Self {
+        self.renderer = self.renderer.with_font(family, size);
+        self
+    }
+
+    /// See [`SvgRenderer::with_source`].
+    pub fn source(mut self, source: String) -> Self {
+        self.renderer = self.renderer.with_source(source);
+        self
+    }
+
+    /// See [`SvgRenderer::interactive`].
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.renderer.interactive = interactive;
+        self
+    }
+
+    /// See [`SvgRenderer::minimal`].
+    pub fn minimal(mut self, minimal: bool) -> Self {
+        self.renderer.group = minimal.then(Group::new);
+        self
+    }
+
+    /// See [`SvgRenderer::presentation`].
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.renderer.barrier_groups = presentation.then(Vec::new);
+        self
+    }
+
+    /// See [`SvgRenderer::with_precision`].
+    pub fn precision(mut self, decimals: Option<u32>) -> Self {
+        self.renderer.precision = decimals;
+        self
+    }
+
+    /// See [`SvgRenderer::from_document`].
+    pub fn document(mut self, document: Document) -> Self {
+        self.renderer.document = document;
+        self
+    }
+
+    pub fn build(self) -> SvgRenderer {
+        self.renderer
+    }
 }
 
 impl SvgRenderer {
@@ -17,77 +100,343 @@ impl SvgRenderer {
         SvgRenderer {
             document: Document::new(),
             stroke_width: 3,
+            source: None,
+            font_family: FONT_FAMILY.to_owned(),
+            font_size: DEFAULT_FONT_SIZE,
+            interactive: false,
+            group: None,
+            precision: Some(2),
+            barrier_groups: None,
         }
     }
+
+    /// Starts configuring a renderer, for when more than one option is set.
+    pub fn builder() -> SvgRendererBuilder {
+        SvgRendererBuilder {
+            renderer: SvgRenderer::new(),
+        }
+    }
+
+    /// Sets the font family of all text, and the size of text drawn
+    /// without one. The sizes the layout sets, from
+    /// [`crate::Options::font_sizes`], are scaled along with it, so that
+    /// the default size of 18 draws text at the sizes of the layout.
+    ///
+    /// The layout measures text at the sizes of its options, as a monospace
+    /// font whose characters are 15/18 of the size wide, and fits its boxes
+    /// and line breaks to those widths. Larger sizes, or proportional or
+    /// wider fonts, may overflow their boxes; to enlarge text along with
+    /// its boxes, raise the sizes of the options instead.
+    pub fn with_font(mut self, family: &str, size: f64) -> Self {
+        self.font_family = family.to_owned();
+        self.font_size = size;
+        self
+    }
+
+    /// Size of the text drawn with the given style.
+    fn font_size(&self, style: &Style) -> Option<f64> {
+        let scale = self.font_size / DEFAULT_FONT_SIZE;
+        style.font_size.map(|size| size * scale)
+    }
+
+    /// Embeds the diagram's source in the SVG's metadata, so the diagram can
+    /// be edited again later. See [`super::extract_svg_source`].
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Makes hovering a barrier or a component dim everything unrelated to
+    /// it, for viewing in a browser. The SVG embeds a small style sheet and
+    /// script for it.
+    pub fn interactive(mut self) -> Self {
+        self.interactive = true;
+        self
+    }
+
+    /// Shrinks the SVG by setting the attributes most elements share, the
+    /// stroke width, font and text fill, once on a `<g>` wrapping the
+    /// diagram, and leaving them out of the elements. The stroke colour
+    /// stays on each element, as text would otherwise inherit an outline.
+    pub fn minimal(mut self) -> Self {
+        self.group = Some(Group::new());
+        self
+    }
+
+    /// Animates the SVG to walk through the barriers when opened in a
+    /// browser, bringing each into focus in turn while the others are
+    /// faded, and starting over after the last. The elements drawn for
+    /// each barrier are grouped, each group cycling its opacity with SMIL
+    /// `<animate>` elements, so no script is needed.
+    pub fn presentation(mut self) -> Self {
+        self.barrier_groups = Some(Vec::new());
+        self
+    }
+
+    /// Rounds every coordinate and size written to the given number of
+    /// decimal places, 2 by default, keeping the SVG short and its diffs
+    /// readable. `None` writes them exactly.
+    ///
+    /// Shapes are rounded by their corners, so edges shared by adjacent
+    /// shapes stay shared and no gaps open between them.
+    pub fn with_precision(mut self, decimals: Option<u32>) -> Self {
+        self.precision = decimals;
+        self
+    }
+
+    /// Creates a renderer that draws on top of an existing document,
+    /// keeping its content. The document's `viewBox` is replaced by the
+    /// diagram's on setup.
+    pub fn from_document(document: Document) -> Self {
+        SvgRenderer {
+            document,
+            ..SvgRenderer::new()
+        }
+    }
+
+    /// Returns the document for further composition, e.g. nesting it
+    /// inside a larger SVG.
+    pub fn into_document(self) -> Document {
+        let mut document = self.document;
+        let mut group = self.group;
+        let barrier_groups = self.barrier_groups.unwrap_or_default();
+        let count = barrier_groups.len();
+        for (i, (_, barrier_group)) in barrier_groups.into_iter().enumerate() {
+            let barrier_group = barrier_group.add(focus_animation(i, count));
+            match &mut group {
+                Some(group) => group.append(barrier_group),
+                None => document.append(barrier_group),
+            }
+        }
+        if let Some(group) = group {
+            // Inherited attributes give way to the elements' own.
+            document = document.add(
+                group
+                    .set("fill", DEFAULT_TEXT_FILL)
+                    .set("stroke-width", self.stroke_width)
+                    .set("font-family", self.font_family.as_str())
+                    .set("font-size", self.font_size),
+            );
+        }
+        if !self.interactive {
+            return document;
+        }
+        document
+            .add(svg::node::element::Style::new(INTERACTIVE_STYLE))
+            .add(Script::new(INTERACTIVE_SCRIPT))
+    }
+
+    /// Adds a drawn element, to the group in minimal mode, or to the group
+    /// of its barrier in presentation mode.
+    fn push<N: Into<Box<dyn Node>>>(&mut self, node: N, style: &Style) {
+        let barrier = style.links.first().filter(|id| id.starts_with("barrier-"));
+        if let (Some(groups), Some(barrier)) = (&mut self.barrier_groups, barrier) {
+            match groups.iter_mut().find(|(id, _)| id == barrier) {
+                Some((_, group)) => group.append(node),
+                None => {
+                    let mut group = Group::new().set("class", "barrier-focus");
+                    group.append(node);
+                    groups.push((barrier.clone(), group));
+                }
+            }
+            return;
+        }
+        match &mut self.group {
+            Some(group) => group.append(node),
+            None => self.document.append(node),
+        }
+    }
+
+    /// Sets an attribute unless minimal mode shares `shared` on the group.
+    fn assign_unshared<N: Node, V: Into<svg::node::Value>>(
+        &self,
+        node: &mut N,
+        name: &str,
+        value: Option<V>,
+        shared: V,
+    ) {
+        match value {
+            Some(value) => node.assign(name, value),
+            None if self.group.is_none() => node.assign(name, shared),
+            None => {}
+        }
+    }
+
+    /// Lists the ids of the elements an element is linked to, for the
+    /// interactive script.
+    fn assign_links<N: Node>(&self, node: &mut N, style: &Style) {
+        if self.interactive && !style.links.is_empty() {
+            node.assign("data-links", style.links.join(" "));
+        }
+    }
+
+    fn assign_stroke_width<N: Node>(&self, node: &mut N, style: &Style) {
+        let shared = self.stroke_width as f64;
+        self.assign_unshared(node, "stroke-width", style.stroke_width, shared);
+    }
+
+    fn assign_font_family<N: Node>(&self, node: &mut N) {
+        self.assign_unshared(node, "font-family", None, self.font_family.clone());
+    }
+
+    fn round(&self, value: f64) -> f64 {
+        let Some(decimals) = self.precision else {
+            return value;
+        };
+        let scale = 10f64.powi(decimals as i32);
+        (value * scale).round() / scale
+    }
+
+    fn round_point(&self, point: &Vector2) -> (f64, f64) {
+        (self.round(point.x), self.round(point.y))
+    }
 }
 
 impl Renderer for SvgRenderer {
+    fn configure(mut self, settings: &RendererSettings) -> Self {
+        if let Some(family) = &settings.font_family {
+            self.font_family = family.clone();
+        }
+        if let Some(width) = settings.stroke_width {
+            self.stroke_width = width;
+        }
+        self
+    }
+
     fn setup(mut self, width: f64, height: f64) -> Self {
-        self.document = Document::new().set("viewBox", (0, 0, width, height));
+        let (width, height) = (self.round(width), self.round(height));
+        self.document = self.document.set("viewBox", (0, 0, width, height));
         self
     }
 
-    fn draw_line(mut self, from: &Vector2, to: &Vector2) -> Self {
-        let data = Data::new().move_to((from.x, from.y)).line_to((to.x, to.y));
-        let path = Path::new()
+    fn draw_line(mut self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let data = Data::new()
+            .move_to(self.round_point(from))
+            .line_to(self.round_point(to));
+        let mut path = Path::new()
             .set("fill", "none")
-            .set("stroke", "black")
-            .set("stroke-width", self.stroke_width)
+            .set("stroke", color_or(style.stroke, DEFAULT_STROKE))
             .set("d", data);
-        self.document = self.document.add(path);
+        self.assign_stroke_width(&mut path, style);
+        assign_dashes(&mut path, style);
+        assign_line_shape(&mut path, style);
+        assign_class(&mut path, style);
+        assign_opacity(&mut path, style);
+        self.assign_links(&mut path, style);
+        self.push(path, style);
         self
     }
 
-    fn draw_circle(mut self, radius: f64, centre: &Vector2) -> Self {
-        let circle = Circle::new()
-            .set("cx", centre.x)
-            .set("cy", centre.y)
-            .set("r", radius)
-            .set("stroke", "black")
-            .set("stroke-width", self.stroke_width)
-            .set("fill", DEFAULT_BG_FILL);
-        self.document = self.document.add(circle);
+    fn draw_polyline(mut self, points: &[Vector2], style: &Style) -> Self {
+        let mut points = points.iter().map(|p| self.round_point(p));
+        let Some(start) = points.next() else {
+            return self;
+        };
+        let mut data = Data::new().move_to(start);
+        for point in points {
+            data = data.line_to(point);
+        }
+        let mut path = Path::new()
+            .set("fill", "none")
+            .set("stroke", color_or(style.stroke, DEFAULT_STROKE))
+            .set("d", data);
+        self.assign_stroke_width(&mut path, style);
+        assign_dashes(&mut path, style);
+        assign_line_shape(&mut path, style);
+        assign_class(&mut path, style);
+        assign_opacity(&mut path, style);
+        self.assign_links(&mut path, style);
+        self.push(path, style);
         self
     }
 
-    fn draw_text(mut self, text: &str, containment: &Rectangle, alignment: Alignment) -> Self {
-        let font_size = 18.0;
-        let width = (text.len() as f64) * font_size / FONT_WIDTH;
-        let y = containment.centre.y + (font_size / (FONT_WIDTH * 2.0));
-        let x = match alignment {
-            Alignment::Center => containment.centre.x - (width / 2.0),
-            Alignment::Left => containment.centre.x - (containment.width / 2.0),
-            Alignment::Right => containment.centre.x + (containment.width / 2.0) - width,
-        };
-        let text = Text::new()
+    fn draw_circle(mut self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let mut circle = Circle::new()
+            .set("cx", self.round(centre.x))
+            .set("cy", self.round(centre.y))
+            .set("r", self.round(radius))
+            .set("stroke", color_or(style.stroke, DEFAULT_STROKE))
+            .set("fill", color_or(style.fill, DEFAULT_BG_FILL));
+        self.assign_stroke_width(&mut circle, style);
+        assign_dashes(&mut circle, style);
+        assign_class(&mut circle, style);
+        assign_opacity(&mut circle, style);
+        self.assign_links(&mut circle, style);
+        self.push(circle, style);
+        self
+    }
+
+    fn draw_text(
+        mut self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let font_size = self.font_size(style);
+        let origin = text_origin(
+            text,
+            font_size.unwrap_or(self.font_size),
+            containment,
+            alignment,
+        );
+        let (x, y) = self.round_point(&origin);
+        let mut text = Text::new()
             .set("x", x)
             .set("y", y)
-            .set("font-size", font_size)
-            .set("fill", "black")
-            .set("font-family", FONT_FAMILY)
             .add(svg::node::Text::new(text));
-        self.document = self.document.add(text);
+        self.assign_unshared(&mut text, "font-size", font_size, self.font_size);
+        let fill = style.fill.map(|fill| fill.to_svg_string());
+        self.assign_unshared(&mut text, "fill", fill, DEFAULT_TEXT_FILL.to_owned());
+        self.assign_font_family(&mut text);
+        if style.italic {
+            text.assign("font-style", "italic");
+        }
+        if style.bold {
+            text.assign("font-weight", "bold");
+        }
+        if style.rotation != 0.0 {
+            let (x, y) = self.round_point(&containment.centre);
+            let transform = format!("rotate({} {x} {y})", style.rotation);
+            text.assign("transform", transform);
+        }
+        assign_class(&mut text, style);
+        assign_opacity(&mut text, style);
+        self.assign_links(&mut text, style);
+        self.push(text, style);
         self
     }
 
-    fn draw_rectangle(mut self, rectangle: &Rectangle) -> Self {
-        let top_left = Vector2 {
+    fn draw_rectangle(mut self, rectangle: &Rectangle, style: &Style) -> Self {
+        // Rounding both corners, rather than the size, keeps the edges of
+        // adjacent rectangles together.
+        let (left, top) = self.round_point(&Vector2 {
             x: rectangle.centre.x - (rectangle.width / 2.0),
             y: rectangle.centre.y - (rectangle.height / 2.0),
-        };
+        });
+        let (right, bottom) = self.round_point(&Vector2 {
+            x: rectangle.centre.x + (rectangle.width / 2.0),
+            y: rectangle.centre.y + (rectangle.height / 2.0),
+        });
+        let (width, height) = (self.round(right - left), self.round(bottom - top));
         let data = Data::new()
-            .move_to((top_left.x, top_left.y))
-            .line_by((rectangle.width, 0))
-            .line_by((0, rectangle.height))
-            .line_by((-rectangle.width, 0))
+            .move_to((left, top))
+            .line_by((width, 0))
+            .line_by((0, height))
+            .line_by((-width, 0))
             .close();
-        let path = Path::new()
-            .set("fill", DEFAULT_BG_FILL)
-            .set("stroke", "black")
-            .set("stroke-width", self.stroke_width)
-            .set("font-family", FONT_FAMILY)
+        let mut path = Path::new()
+            .set("fill", color_or(style.fill, DEFAULT_BG_FILL))
+            .set("stroke", color_or(style.stroke, DEFAULT_STROKE))
             .set("d", data);
-        self.document = self.document.add(path);
+        self.assign_stroke_width(&mut path, style);
+        self.assign_font_family(&mut path);
+        assign_dashes(&mut path, style);
+        assign_line_shape(&mut path, style);
+        assign_class(&mut path, style);
+        assign_opacity(&mut path, style);
+        self.assign_links(&mut path, style);
+        self.push(path, style);
         self
     }
 
@@ -96,15 +445,143 @@ impl Renderer for SvgRenderer {
         text: &str,
         rectangle: &Rectangle,
         alignment: Alignment,
+        style: &Style,
     ) -> Self {
-        self = self.draw_rectangle(&rectangle.with_padding(2.0));
-        self = self.draw_text(text, &rectangle, alignment);
+        let text_style = Style {
+            fill: style.fill.map(|fill| fill.contrasting_text()),
+            font_size: style.font_size,
+            class: style.class,
+            tooltip: style.tooltip.clone(),
+            links: style.links.clone(),
+            opacity: style.opacity,
+            ..Style::default()
+        };
+        self = self.draw_rectangle(&rectangle.with_padding(2.0), style);
+        self = self.draw_text(text, &rectangle, alignment, &text_style);
+        self
+    }
+
+    fn draw_icon(mut self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let mut data = Data::new();
+        for ring in icon.outline(rectangle) {
+            let mut points = ring.iter().map(|p| self.round_point(p));
+            let Some(start) = points.next() else {
+                continue;
+            };
+            data = data.move_to(start);
+            for point in points {
+                data = data.line_to(point);
+            }
+            data = data.close();
+        }
+        let mut path = Path::new()
+            .set("fill-rule", "evenodd")
+            .set("stroke", "none")
+            .set("d", data);
+        let fill = style.fill.map(|fill| fill.to_svg_string());
+        self.assign_unshared(&mut path, "fill", fill, DEFAULT_TEXT_FILL.to_owned());
+        assign_class(&mut path, style);
+        assign_opacity(&mut path, style);
+        self.assign_links(&mut path, style);
+        self.push(path, style);
         self
     }
 
     fn into_bytes(self) -> Vec<u8> {
         let mut bytes = Vec::<u8>::new();
-        svg::write(&mut bytes, &self.document).unwrap();
+        self.render_into(&mut bytes);
         bytes
     }
+
+    fn render_into(mut self, buffer: &mut Vec<u8>) {
+        let source = self.source.take();
+        let start = buffer.len();
+        svg::write(&mut *buffer, &self.into_document()).unwrap();
+        let Some(source) = source else {
+            return;
+        };
+        // The metadata is spliced in as raw CDATA, which the svg crate would
+        // otherwise escape.
+        let Some(position) = find_root_tag_end(&buffer[start..]) else {
+            return;
+        };
+        let position = start + position;
+        let metadata = format!(
+            "\n{SOURCE_METADATA_START}{}{SOURCE_METADATA_END}",
+            wrap_cdata(&source)
+        );
+        buffer.splice(position..position, metadata.into_bytes());
+    }
+}
+
+/// Returns the animation bringing the `i`th of `count` barriers into focus
+/// for its step of the cycle. Values are held between key times, so the
+/// opacity switches at the start and end of the step.
+fn focus_animation(i: usize, count: usize) -> Animate {
+    let (start, end) = (i as f64 / count as f64, (i + 1) as f64 / count as f64);
+    let mut key_times = vec![0.0];
+    let mut values = Vec::new();
+    if start > 0.0 {
+        values.push(UNFOCUSED_OPACITY);
+        key_times.push(start);
+    }
+    values.push(1.0);
+    if end < 1.0 {
+        values.push(UNFOCUSED_OPACITY);
+        key_times.push(end);
+    }
+    let join = |numbers: Vec<f64>| {
+        numbers
+            .iter()
+            .map(f64::to_string)
+            .collect::<Vec<_>>()
+            .join(";")
+    };
+    Animate::new()
+        .set("attributeName", "opacity")
+        .set("calcMode", "discrete")
+        .set("values", join(values))
+        .set("keyTimes", join(key_times))
+        .set("dur", format!("{}s", FOCUS_STEP * count as f64))
+        .set("repeatCount", "indefinite")
+}
+
+/// Returns the position just after the opening `<svg ...>` tag.
+fn find_root_tag_end(bytes: &[u8]) -> Option<usize> {
+    let start = bytes.windows(4).position(|w| w == b"<svg")?;
+    let length = bytes[start..].iter().position(|b| *b == b'>')?;
+    Some(start + length + 1)
+}
+
+fn assign_dashes<N: Node>(node: &mut N, style: &Style) {
+    if style.dashed {
+        node.assign("stroke-dasharray", DASH_ARRAY);
+    }
+}
+
+fn assign_line_shape<N: Node>(node: &mut N, style: &Style) {
+    if let Some(line_cap) = style.line_cap {
+        node.assign("stroke-linecap", line_cap.to_svg_string());
+    }
+    if let Some(line_join) = style.line_join {
+        node.assign("stroke-linejoin", line_join.to_svg_string());
+    }
+}
+
+fn assign_opacity<N: Node>(node: &mut N, style: &Style) {
+    if let Some(opacity) = style.opacity {
+        node.assign("opacity", opacity);
+    }
+}
+
+/// Sets the semantic class of an element so it can be styled with CSS.
+/// Inline presentation attributes are kept as fallbacks. The tooltip, if
+/// any, is added as a `<title>` child.
+fn assign_class<N: Node>(node: &mut N, style: &Style) {
+    if let Some(class) = style.class {
+        node.assign("class", class);
+    }
+    if let Some(tooltip) = &style.tooltip {
+        node.append(Title::new().add(svg::node::Text::new(tooltip.as_str())));
+    }
 }
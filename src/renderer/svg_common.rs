@@ -0,0 +1,66 @@
+//! Markup shared by the SVG renderers.
+use crate::renderer::Color;
+
+pub(crate) const FONT_FAMILY: &str = "Courier, monospace";
+pub(crate) const DEFAULT_BG_FILL: &str = "white";
+pub(crate) const DEFAULT_STROKE: &str = "black";
+pub(crate) const DEFAULT_TEXT_FILL: &str = "black";
+pub(crate) const DASH_ARRAY: &str = "10,6";
+pub(crate) const SOURCE_METADATA_START: &str = "<metadata class=\"bowtie-source\">";
+pub(crate) const SOURCE_METADATA_END: &str = "</metadata>";
+pub(crate) const CDATA_START: &str = "<![CDATA[";
+pub(crate) const CDATA_END: &str = "]]>";
+pub(crate) const INTERACTIVE_STYLE: &str = "\
+[data-links] { transition: opacity 0.2s; }
+.bowtie-dimmed { opacity: 0.2; }";
+/// Dims every linked element not sharing the hovered element's own id.
+pub(crate) const INTERACTIVE_SCRIPT: &str = "\
+(function () {
+  var script = document.currentScript;
+  var root = (script ? script.closest('svg') : null) || document;
+  var elements = root.querySelectorAll('[data-links]');
+  function links(element) {
+    return element.getAttribute('data-links').split(' ');
+  }
+  elements.forEach(function (element) {
+    var id = links(element)[0];
+    element.addEventListener('mouseenter', function () {
+      elements.forEach(function (other) {
+        other.classList.toggle('bowtie-dimmed', links(other).indexOf(id) === -1);
+      });
+    });
+    element.addEventListener('mouseleave', function () {
+      elements.forEach(function (other) {
+        other.classList.remove('bowtie-dimmed');
+      });
+    });
+  });
+})();";
+
+/// Extracts the diagram source embedded by `with_source` on either SVG
+/// renderer from an SVG, if there is one.
+pub fn extract_svg_source(svg: &str) -> Option<String> {
+    let start = svg.find(SOURCE_METADATA_START)? + SOURCE_METADATA_START.len();
+    let length = svg[start..].find(SOURCE_METADATA_END)?;
+    let mut metadata = &svg[start..start + length];
+    let mut source = String::new();
+    while let Some(section_start) = metadata.find(CDATA_START) {
+        let content = &metadata[section_start + CDATA_START.len()..];
+        let section_length = content.find(CDATA_END)?;
+        source.push_str(&content[..section_length]);
+        metadata = &content[section_length + CDATA_END.len()..];
+    }
+    Some(source)
+}
+
+/// Wraps text in CDATA, splitting the sections around any `]]>` it contains.
+pub(crate) fn wrap_cdata(text: &str) -> String {
+    let escaped = text.replace(CDATA_END, "]]]]><![CDATA[>");
+    format!("{CDATA_START}{escaped}{CDATA_END}")
+}
+
+pub(crate) fn color_or(color: Option<Color>, default: &str) -> String {
+    color
+        .map(|c| c.to_svg_string())
+        .unwrap_or(default.to_owned())
+}
@@ -0,0 +1,140 @@
+use crate::renderer::{Alignment, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2};
+
+/// Forwards every draw call to two renderers, so that a diagram is laid out
+/// once and drawn in two formats, e.g. SVG alongside a JSON layout.
+///
+/// `into_bytes` returns the primary renderer's output. To keep both, render
+/// with [`crate::render_bowtie`] and call [`TeeRenderer::into_both_bytes`].
+pub struct TeeRenderer<A, B> {
+    primary: A,
+    secondary: B,
+}
+
+impl<A, B> TeeRenderer<A, B>
+where
+    A: Renderer,
+    B: Renderer,
+{
+    pub fn new(primary: A, secondary: B) -> Self {
+        TeeRenderer { primary, secondary }
+    }
+
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    pub fn into_parts(self) -> (A, B) {
+        (self.primary, self.secondary)
+    }
+
+    /// Returns the output of the primary and secondary renderers.
+    pub fn into_both_bytes(self) -> (Vec<u8>, Vec<u8>) {
+        (self.primary.into_bytes(), self.secondary.into_bytes())
+    }
+}
+
+impl<A, B> Renderer for TeeRenderer<A, B>
+where
+    A: Renderer,
+    B: Renderer,
+{
+    fn configure(self, settings: &RendererSettings) -> Self {
+        TeeRenderer {
+            primary: self.primary.configure(settings),
+            secondary: self.secondary.configure(settings),
+        }
+    }
+
+    fn setup(self, width: f64, height: f64) -> Self {
+        TeeRenderer {
+            primary: self.primary.setup(width, height),
+            secondary: self.secondary.setup(width, height),
+        }
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_line(from, to, style),
+            secondary: self.secondary.draw_line(from, to, style),
+        }
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_polyline(points, style),
+            secondary: self.secondary.draw_polyline(points, style),
+        }
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_circle(radius, centre, style),
+            secondary: self.secondary.draw_circle(radius, centre, style),
+        }
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_text(text, containment, alignment, style),
+            secondary: self
+                .secondary
+                .draw_text(text, containment, alignment, style),
+        }
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_rectangle(rectangle, style),
+            secondary: self.secondary.draw_rectangle(rectangle, style),
+        }
+    }
+
+    fn draw_text_with_rectangle(
+        self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        TeeRenderer {
+            primary: self
+                .primary
+                .draw_text_with_rectangle(text, rectangle, alignment, style),
+            secondary: self
+                .secondary
+                .draw_text_with_rectangle(text, rectangle, alignment, style),
+        }
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        TeeRenderer {
+            primary: self.primary.draw_icon(icon, rectangle, style),
+            secondary: self.secondary.draw_icon(icon, rectangle, style),
+        }
+    }
+
+    fn finalize(self) -> Self {
+        TeeRenderer {
+            primary: self.primary.finalize(),
+            secondary: self.secondary.finalize(),
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.primary.into_bytes()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        self.primary.render_into(buffer)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::renderer::{
+    Alignment, Color, Icon, Rectangle, Renderer, RendererSettings, Style, Vector2,
+};
+
+/// Length of each arm of a crop mark.
+const CROP_MARK_LENGTH: f64 = 12.0;
+const CROP_MARK_STROKE_WIDTH: f64 = 0.5;
+
+/// Draws one tile of a larger canvas: everything drawn through it is moved
+/// so that the tile's top left corner is the origin, and the canvas is set
+/// up at the tile's size, leaving out whatever falls outside it. Crop marks
+/// are drawn at the tile's corners when finalized, for trimming and joining
+/// printed tiles.
+pub(crate) struct TileRenderer<R> {
+    inner: R,
+    tile: Rectangle,
+}
+
+impl<R> TileRenderer<R>
+where
+    R: Renderer,
+{
+    pub fn new(inner: R, tile: Rectangle) -> Self {
+        TileRenderer { inner, tile }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn point(&self, v: &Vector2) -> Vector2 {
+        Vector2 {
+            x: v.x - (self.tile.centre.x - self.tile.width / 2.0),
+            y: v.y - (self.tile.centre.y - self.tile.height / 2.0),
+        }
+    }
+
+    fn rectangle(&self, rectangle: &Rectangle) -> Rectangle {
+        Rectangle {
+            centre: self.point(&rectangle.centre),
+            ..*rectangle
+        }
+    }
+
+    fn map(self, draw: impl FnOnce(R) -> R) -> Self {
+        TileRenderer {
+            inner: draw(self.inner),
+            ..self
+        }
+    }
+}
+
+impl<R> Renderer for TileRenderer<R>
+where
+    R: Renderer,
+{
+    fn configure(self, settings: &RendererSettings) -> Self {
+        self.map(|r| r.configure(settings))
+    }
+
+    fn setup(self, _width: f64, _height: f64) -> Self {
+        let (width, height) = (self.tile.width, self.tile.height);
+        self.map(|r| r.setup(width, height))
+    }
+
+    fn draw_line(self, from: &Vector2, to: &Vector2, style: &Style) -> Self {
+        let (from, to) = (self.point(from), self.point(to));
+        self.map(|r| r.draw_line(&from, &to, style))
+    }
+
+    fn draw_polyline(self, points: &[Vector2], style: &Style) -> Self {
+        let points = points.iter().map(|p| self.point(p)).collect::<Vec<_>>();
+        self.map(|r| r.draw_polyline(&points, style))
+    }
+
+    fn draw_circle(self, radius: f64, centre: &Vector2, style: &Style) -> Self {
+        let centre = self.point(centre);
+        self.map(|r| r.draw_circle(radius, &centre, style))
+    }
+
+    fn draw_text(
+        self,
+        text: &str,
+        containment: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let containment = self.rectangle(containment);
+        self.map(|r| r.draw_text(text, &containment, alignment, style))
+    }
+
+    fn draw_rectangle(self, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_rectangle(&rectangle, style))
+    }
+
+    fn draw_text_with_rectangle(
+        self,
+        text: &str,
+        rectangle: &Rectangle,
+        alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_text_with_rectangle(text, &rectangle, alignment, style))
+    }
+
+    fn draw_icon(self, icon: Icon, rectangle: &Rectangle, style: &Style) -> Self {
+        let rectangle = self.rectangle(rectangle);
+        self.map(|r| r.draw_icon(icon, &rectangle, style))
+    }
+
+    fn finalize(self) -> Self {
+        let (width, height) = (self.tile.width, self.tile.height);
+        let style = Style {
+            stroke: Some(Color::BLACK),
+            stroke_width: Some(CROP_MARK_STROKE_WIDTH),
+            ..Style::with_class("crop-mark")
+        };
+        // Each corner gets an arm along both of its edges, pointing inwards.
+        let inwards = |at_start: bool| {
+            if at_start {
+                CROP_MARK_LENGTH
+            } else {
+                -CROP_MARK_LENGTH
+            }
+        };
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)];
+        self.map(|mut r| {
+            for (x, y) in corners {
+                let corner = Vector2 { x, y };
+                let arm_x = x + inwards(x == 0.0);
+                let arm_y = y + inwards(y == 0.0);
+                r = r.draw_line(&corner, &Vector2 { x: arm_x, y }, &style);
+                r = r.draw_line(&corner, &Vector2 { x, y: arm_y }, &style);
+            }
+            r.finalize()
+        })
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.inner.into_bytes()
+    }
+
+    fn render_into(self, buffer: &mut Vec<u8>) {
+        self.inner.render_into(buffer)
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{Barrier, BarrierState, Component, ComponentKind, Diagram, Keywords};
+use std::fmt::Write;
+
+impl Diagram {
+    /// Writes the diagram back into the DSL, so that parsing the output
+    /// yields an equal diagram. Useful to format documents in editors.
+    ///
+    /// Values set with [`Diagram::set_metadata`] aren't written, as only the
+    /// handlers of the custom commands that set them know their syntax.
+    pub fn to_dsl(&self) -> String {
+        self.to_dsl_with_keywords(&Keywords::default())
+    }
+
+    /// Writes the diagram into the DSL using the given keywords. See
+    /// [`crate::parse_diagram_with_keywords`].
+    pub fn to_dsl_with_keywords(&self, keywords: &Keywords) -> String {
+        let mut lines = Vec::new();
+        if !self.title.is_empty() {
+            lines.push(format!("{} {}", keywords.title, self.title));
+        }
+        if !self.event.is_empty() {
+            let mut line = format!("{} {}", keywords.event, self.event);
+            if let Some(severity) = self.event_severity {
+                write!(line, " | severity {severity}").unwrap();
+            }
+            lines.push(line);
+        }
+        if let Some(radius) = self.event_radius {
+            lines.push(format!("{} {radius}", keywords.event_radius));
+        }
+        if let Some(rating) = self.rating {
+            lines.push(format!(
+                "{} L{} S{}",
+                keywords.rating, rating.likelihood, rating.severity
+            ));
+        }
+        push_section(&mut lines);
+        for component in &self.components {
+            let keyword = match component.kind {
+                ComponentKind::Cause => &keywords.cause,
+                ComponentKind::Consequence => &keywords.consequence,
+            };
+            let mut line = format!("{keyword} {}", component.name);
+            if let Some(score) = component.score {
+                write!(line, " | score {score}").unwrap();
+            }
+            if let Some(likelihood) = component.likelihood {
+                write!(line, " | likelihood {likelihood}").unwrap();
+            }
+            lines.push(line);
+        }
+        push_section(&mut lines);
+        for component in &self.components {
+            let Some(name) = self.command_name(component, keywords, |c| &c.pathway) else {
+                continue;
+            };
+            if let Some(pathway) = &component.pathway {
+                lines.push(format!("{} {name}: {pathway}", keywords.pathway));
+            }
+        }
+        for component in &self.components {
+            let Some(name) = self.command_name(component, keywords, |c| &c.description) else {
+                continue;
+            };
+            if let Some(description) = &component.description {
+                lines.push(format!("{} {name}: {description}", keywords.describe));
+            }
+        }
+        let mut categories: Vec<(&str, Vec<String>)> = Vec::new();
+        for component in &self.components {
+            let Some(category) = component.category.as_deref() else {
+                continue;
+            };
+            let Some(name) = self.command_name(component, keywords, |c| &c.category) else {
+                continue;
+            };
+            match categories.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, names)) => names.push(name),
+                None => categories.push((category, vec![name])),
+            }
+        }
+        for (category, names) in categories {
+            lines.push(format!(
+                "{} {category}: {}",
+                keywords.category,
+                names.join(", ")
+            ));
+        }
+        for component in &self.components {
+            let Some(name) = self.command_name(component, keywords, |c| &c.gates) else {
+                continue;
+            };
+            for gate in &component.gates {
+                let separator = format!(" {} ", gate.operator.as_str());
+                lines.push(format!(
+                    "{} {name}: {}",
+                    keywords.gate,
+                    gate.barriers.join(&separator)
+                ));
+            }
+        }
+        for component in &self.components {
+            let Some(name) = self.command_name(component, keywords, |c| &c.icon) else {
+                continue;
+            };
+            if let Some(icon) = component.icon {
+                lines.push(format!("{} {name}: {}", keywords.icon, icon.name()));
+            }
+        }
+        for component in &self.components {
+            if component.route.is_empty() {
+                continue;
+            }
+            let Some(name) = self.command_name(component, keywords, |c| &c.route) else {
+                continue;
+            };
+            let waypoints = component
+                .route
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "{} {name}: {}",
+                keywords.route,
+                waypoints.join("; ")
+            ));
+        }
+        push_section(&mut lines);
+        lines.extend(self.barrier_lines(keywords));
+        push_section(&mut lines);
+        for note in &self.notes {
+            lines.push(format!("{} {note}", keywords.note));
+        }
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        let mut dsl = lines.join("\n");
+        dsl.push('\n');
+        dsl
+    }
+
+    /// Names the component in the command setting one of its attributes.
+    /// Commands apply to every component of their name, so a name used on
+    /// both sides is written once if both components agree on the
+    /// attribute, giving `None` for the second of them, and qualified with
+    /// the keyword of its kind otherwise.
+    fn command_name<T: PartialEq>(
+        &self,
+        component: &Component,
+        keywords: &Keywords,
+        attribute: impl Fn(&Component) -> &T,
+    ) -> Option<String> {
+        let other = self
+            .components
+            .iter()
+            .find(|c| c.name == component.name && c.kind != component.kind);
+        let Some(other) = other else {
+            return Some(component.name.clone());
+        };
+        if attribute(component) == attribute(other) {
+            let first = self.components.iter().find(|c| c.name == component.name)?;
+            return (first.kind == component.kind).then(|| component.name.clone());
+        }
+        let keyword = match component.kind {
+            ComponentKind::Cause => &keywords.cause,
+            ComponentKind::Consequence => &keywords.consequence,
+        };
+        Some(format!("{keyword} {}", component.name))
+    }
+
+    /// Declares each barrier once, attached to the components it protects.
+    /// Components list barriers in the order they were attached, so any
+    /// attached out of declaration order are attached again afterwards,
+    /// with bare declarations that leave the barrier's attributes alone.
+    /// Components whose name is used on both sides are attached with the
+    /// preventive or recovery keyword, naming their side.
+    fn barrier_lines(&self, keywords: &Keywords) -> Vec<String> {
+        let mut lines = Vec::new();
+        let is_shared = |name: &str| {
+            self.has_component_of(name, Some(ComponentKind::Cause))
+                && self.has_component_of(name, Some(ComponentKind::Consequence))
+        };
+        // Index of the next barrier to attach to each component.
+        let mut attached = vec![0; self.components.len()];
+        for barrier in &self.barriers {
+            let mut names = Vec::new();
+            for (component, next) in self.components.iter().zip(&mut attached) {
+                if is_shared(&component.name) {
+                    continue;
+                }
+                if component.barriers.get(*next) == Some(&barrier.name) {
+                    names.push(format_attachment(component, &barrier.name));
+                    *next += 1;
+                }
+            }
+            let line = format!(
+                "{} {}: {}",
+                keywords.barrier,
+                format_barrier(barrier),
+                names.join(", ")
+            );
+            lines.push(line.trim_end().to_owned());
+        }
+        for (component, next) in self.components.iter().zip(attached) {
+            let keyword = match component.kind {
+                _ if !is_shared(&component.name) => &keywords.barrier,
+                ComponentKind::Cause => &keywords.preventive,
+                ComponentKind::Consequence => &keywords.recovery,
+            };
+            for barrier in &component.barriers[next..] {
+                let name = format_attachment(component, barrier);
+                lines.push(format!("{keyword} {barrier}: {name}"));
+            }
+        }
+        lines
+    }
+}
+
+/// Formats a component's name as attached to a barrier, with a leading `+`
+/// if it's only associated with it.
+fn format_attachment(component: &Component, barrier: &str) -> String {
+    if component.associated_barriers.iter().any(|b| b == barrier) {
+        format!("+{}", component.name)
+    } else {
+        component.name.clone()
+    }
+}
+
+/// Formats a barrier's name and attributes, without its components.
+fn format_barrier(barrier: &Barrier) -> String {
+    let mut declaration = if barrier.absent {
+        format!("~{}", barrier.name)
+    } else {
+        barrier.name.clone()
+    };
+    if let Some(state) = barrier.state {
+        let state = match state {
+            BarrierState::Effective => "effective",
+            BarrierState::Degraded => "degraded",
+            BarrierState::Failed => "failed",
+        };
+        write!(declaration, " [{state}]").unwrap();
+    }
+    if let Some(condition) = &barrier.condition {
+        write!(declaration, " ?{{if {condition}}}").unwrap();
+    }
+    if let Some(group) = &barrier.group {
+        write!(declaration, " #{group}").unwrap();
+    }
+    if let Some(effectiveness) = barrier.effectiveness {
+        write!(declaration, " | effectiveness {effectiveness}").unwrap();
+    }
+    if barrier.central {
+        declaration.push_str(" | central");
+    }
+    declaration
+}
+
+/// Separates sections with a blank line, unless the last one was empty.
+fn push_section(lines: &mut Vec<String>) {
+    if lines.last().is_some_and(|l| !l.is_empty()) {
+        lines.push(String::new());
+    }
+}
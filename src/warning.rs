@@ -0,0 +1,81 @@
+use crate::{Diagram, Options};
+use std::fmt;
+
+/// Non-fatal problem found while generating a diagram, such as a line that
+/// couldn't be parsed. The problem is worked around, so that a best-effort
+/// diagram is still produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    line: Option<usize>,
+    message: String,
+}
+
+impl Warning {
+    pub(crate) fn new(line: Option<usize>, message: String) -> Self {
+        Warning { line, message }
+    }
+
+    /// Line of the input the problem was found on, starting at 1, if it is
+    /// tied to one.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Diagram {
+    /// Checks the diagram for likely mistakes, currently barriers that
+    /// protect no component, which usually come from a misspelled component
+    /// name in their declaration.
+    pub fn validate(&self) -> Vec<Warning> {
+        self.barriers
+            .iter()
+            .filter(|barrier| {
+                !self
+                    .components
+                    .iter()
+                    .any(|c| c.barriers.contains(&barrier.name))
+            })
+            .map(|barrier| {
+                let message = format!("barrier `{}` protects no component", barrier.name);
+                Warning::new(None, message)
+            })
+            .collect()
+    }
+
+    /// Reports the labels that will be cut short when drawn with the given
+    /// options.
+    pub(crate) fn check_labels(&self, options: &Options, warnings: &mut Vec<Warning>) {
+        let Some(max_chars) = options.max_label_chars else {
+            return;
+        };
+        let components = self.components.iter().map(|c| &c.name);
+        let barriers = self.barriers.iter().map(|b| &b.name);
+        for label in components.chain(barriers) {
+            if label.chars().count() > max_chars {
+                let message = format!("label `{label}` is cut short to {max_chars} characters");
+                warnings.push(Warning::new(None, message));
+            }
+        }
+    }
+}
+
+/// Logs warnings that no caller asked to collect.
+pub(crate) fn log(_warnings: &[Warning]) {
+    #[cfg(feature = "logging")]
+    for warning in _warnings {
+        tracing::warn!(line = warning.line, "{}", warning.message);
+    }
+}
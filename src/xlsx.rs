@@ -0,0 +1,115 @@
+use crate::{Barrier, Component, ComponentKind, Diagram};
+use calamine::{open_workbook, Data, Reader, Xlsx, XlsxError};
+use std::fmt;
+use std::path::Path;
+
+/// Error returned by [`Diagram::from_xlsx`].
+#[derive(Debug)]
+pub enum XlsxImportError {
+    /// The workbook or the sheet couldn't be read.
+    Read(XlsxError),
+    /// The sheet has no header row, or no components below it.
+    EmptySheet,
+    /// The header row lacks one of the required columns.
+    MissingColumn(&'static str),
+    /// A row's type is neither a cause nor a consequence. Rows are counted
+    /// from 1, as spreadsheets number them.
+    InvalidType { row: usize, value: String },
+}
+
+impl fmt::Display for XlsxImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XlsxImportError::Read(e) => write!(f, "failed to read the sheet: {e}"),
+            XlsxImportError::EmptySheet => f.write_str("the sheet has no components"),
+            XlsxImportError::MissingColumn(name) => write!(f, "missing column `{name}`"),
+            XlsxImportError::InvalidType { row, value } => write!(
+                f,
+                "row {row}: expected `cause` or `consequence`, found `{value}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for XlsxImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XlsxImportError::Read(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Diagram {
+    /// Builds a diagram from a sheet of an Excel risk register. The first
+    /// row names the columns, in any order: `Type`, either cause (or
+    /// threat) or consequence, `Item`, the component's name, and `Barrier`,
+    /// a barrier protecting it. A component or barrier listed on several
+    /// rows collects the barriers or components of all of them. An optional
+    /// `Event` column names the event, from its first non-empty cell.
+    ///
+    /// Rows without an item are skipped.
+    pub fn from_xlsx<P>(path: P, sheet: &str) -> Result<Diagram, XlsxImportError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut workbook: Xlsx<_> = open_workbook(path).map_err(XlsxImportError::Read)?;
+        let range = workbook
+            .worksheet_range(sheet)
+            .map_err(XlsxImportError::Read)?;
+        let rows = range
+            .rows()
+            .map(|row| row.iter().map(Data::to_string).collect());
+        parse_register(rows)
+    }
+}
+
+/// Builds a diagram from the rows of a register, the header first.
+fn parse_register<I>(mut rows: I) -> Result<Diagram, XlsxImportError>
+where
+    I: Iterator<Item = Vec<String>>,
+{
+    let header = rows.next().ok_or(XlsxImportError::EmptySheet)?;
+    let find_column = |name: &str| {
+        header
+            .iter()
+            .position(|cell| cell.trim().eq_ignore_ascii_case(name))
+    };
+    let require_column =
+        |name: &'static str| find_column(name).ok_or(XlsxImportError::MissingColumn(name));
+    let type_column = require_column("Type")?;
+    let item_column = require_column("Item")?;
+    let barrier_column = require_column("Barrier")?;
+    let event_column = find_column("Event");
+    let mut diagram = Diagram::default();
+    for (i, row) in rows.enumerate() {
+        let cell = |column: usize| row.get(column).map_or("", |cell| cell.trim());
+        if diagram.event.is_empty() {
+            diagram.event = event_column.map_or("", cell).to_owned();
+        }
+        let item = cell(item_column);
+        if item.is_empty() {
+            continue;
+        }
+        let kind = match cell(type_column).to_lowercase().as_str() {
+            "cause" | "threat" => ComponentKind::Cause,
+            "consequence" => ComponentKind::Consequence,
+            _ => {
+                return Err(XlsxImportError::InvalidType {
+                    // One for the header, one as rows count from 1.
+                    row: i + 2,
+                    value: cell(type_column).to_owned(),
+                });
+            }
+        };
+        diagram.declare_component(Component::new(item, kind));
+        let barrier = cell(barrier_column);
+        if !barrier.is_empty() {
+            diagram.declare_barrier(Barrier::new(barrier), &[item], &[], Some(kind));
+        }
+    }
+    if diagram.components.is_empty() {
+        return Err(XlsxImportError::EmptySheet);
+    }
+    Ok(diagram)
+}
@@ -0,0 +1,47 @@
+use bowtie::renderer::{RawSvgRenderer, Renderer};
+use bowtie::{render_bowtie, Options};
+
+const INPUT: &str = "\
+cause Overfill
+cause Corrosion
+consequence Injury
+event Loss of containment
+barrier Zoning: Overfill
+barrier Level alarm: Overfill
+barrier Inspection: Corrosion
+barrier Evacuation: Injury
+barrier Assembly point: Injury
+";
+
+const DECLARED: [&str; 5] = [
+    "Zoning",
+    "Level alarm",
+    "Inspection",
+    "Evacuation",
+    "Assembly point",
+];
+
+fn render() -> String {
+    let bytes = render_bowtie(INPUT, RawSvgRenderer::new(), &Options::default())
+        .unwrap()
+        .into_bytes();
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Returns the barriers in the order their labels are drawn.
+fn label_order(svg: &str) -> Vec<&'static str> {
+    let mut barriers = DECLARED.to_vec();
+    barriers.sort_by_key(|name| svg.find(name).unwrap());
+    barriers
+}
+
+// Every barrier protects a single component, so that none is listed
+// earlier for protecting more of them.
+#[test]
+fn barriers_render_in_declaration_order_across_runs() {
+    let first = render();
+    assert_eq!(label_order(&first), DECLARED);
+    for _ in 0..20 {
+        assert_eq!(render(), first);
+    }
+}
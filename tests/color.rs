@@ -0,0 +1,71 @@
+use bowtie::renderer::Color;
+use bowtie::Options;
+
+#[test]
+fn parses_short_hex_colors() {
+    assert_eq!(Color::parse("#f80"), Ok(Color::rgb(0xff, 0x88, 0x00)));
+    assert_eq!(Color::parse("#FFF"), Ok(Color::WHITE));
+}
+
+#[test]
+fn parses_long_hex_colors() {
+    assert_eq!(Color::parse("#0072b2"), Ok(Color::rgb(0x00, 0x72, 0xb2)));
+    assert_eq!(Color::parse(" #0072B2 "), Ok(Color::rgb(0x00, 0x72, 0xb2)));
+}
+
+#[test]
+fn parses_rgb_functions() {
+    assert_eq!(
+        Color::parse("rgb(0, 114, 178)"),
+        Ok(Color::rgb(0, 114, 178))
+    );
+    assert_eq!(Color::parse("RGB(255,255,255)"), Ok(Color::WHITE));
+}
+
+#[test]
+fn parses_color_names() {
+    assert_eq!(Color::parse("black"), Ok(Color::BLACK));
+    assert_eq!(Color::parse("Grey"), Color::parse("gray"));
+    assert_eq!(Color::parse("red"), Ok(Color::rgb(0xff, 0x00, 0x00)));
+}
+
+#[test]
+fn rejects_malformed_colors() {
+    for value in [
+        "",
+        "#",
+        "#ff",
+        "#ff00",
+        "#gggggg",
+        "rgb(1, 2)",
+        "rgb(1, 2, 3, 4)",
+        "rgb(256, 0, 0)",
+        "rgb(1, 2, 3",
+        "blurple",
+    ] {
+        let error = Color::parse(value).unwrap_err();
+        assert_eq!(error.to_string(), format!("invalid colour: {value}"));
+    }
+}
+
+#[test]
+fn svg_strings_parse_back_to_the_same_color() {
+    for color in [
+        Color::BLACK,
+        Color::WHITE,
+        Color::rgb(0x00, 0x72, 0xb2),
+        Color::rgb(0xe6, 0x9f, 0x00),
+    ] {
+        assert_eq!(Color::parse(&color.to_svg_string()), Ok(color));
+    }
+}
+
+#[test]
+fn front_matter_colors_go_through_color_parse() {
+    let input = "---\nspine_color: rgb(0, 114, 178)\n---\ncause Overfill\n";
+    let options = Options::default().with_front_matter(input).unwrap();
+    assert_eq!(options.spine_color, Some(Color::rgb(0, 114, 178)));
+    let input = "---\nspine_color: blurple\n---\ncause Overfill\n";
+    let error = Options::default().with_front_matter(input).unwrap_err();
+    assert!(error.to_string().contains("invalid colour: blurple"));
+}
@@ -0,0 +1,81 @@
+use bowtie::renderer::RawSvgRenderer;
+use bowtie::{Diagram, DiagramDiff, Options};
+
+const PREVIOUS: &str = "\
+event Loss of containment
+cause Overfill
+cause Corrosion
+consequence Injury
+barrier Level alarm: Overfill
+barrier Inspection: Corrosion
+barrier Evacuation: Injury
+";
+
+const CURRENT: &str = "\
+event Loss of containment
+cause Overfill
+consequence Injury
+consequence Fire
+barrier Evacuation: Injury
+barrier Sprinklers: Fire
+";
+
+fn render_diff(current: &str, previous: &str) -> String {
+    let current = Diagram::parse(current);
+    let previous = Diagram::parse(previous);
+    let svg = current.render_diff(&previous, RawSvgRenderer::new(), &Options::default());
+    String::from_utf8(svg).unwrap()
+}
+
+#[test]
+fn diff_lists_added_and_removed_elements() {
+    let diff = Diagram::parse(CURRENT).diff(&Diagram::parse(PREVIOUS));
+    assert_eq!(
+        diff,
+        DiagramDiff {
+            added_causes: vec![],
+            removed_causes: vec!["Corrosion".to_owned()],
+            added_consequences: vec!["Fire".to_owned()],
+            removed_consequences: vec![],
+            added_barriers: vec!["Sprinklers".to_owned()],
+            removed_barriers: vec!["Level alarm".to_owned(), "Inspection".to_owned()],
+        }
+    );
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn diff_of_unchanged_diagrams_is_empty() {
+    let diagram = Diagram::parse(PREVIOUS);
+    assert!(diagram.diff(&diagram).is_empty());
+    assert!(DiagramDiff::default().is_empty());
+    // Attributes other than names aren't compared.
+    let scored = Diagram::parse(&PREVIOUS.replace("cause Overfill", "cause Overfill | score 0.5"));
+    assert!(scored.diff(&diagram).is_empty());
+}
+
+#[test]
+fn render_diff_draws_removed_components_and_barriers_back_in() {
+    let svg = render_diff(CURRENT, PREVIOUS);
+    assert_eq!(
+        svg.matches(r#"class="component component-removed""#)
+            .count(),
+        2
+    );
+    assert_eq!(
+        svg.matches(r#"class="component component-added""#).count(),
+        2
+    );
+    assert!(svg.contains("Corrosion"));
+    // Level alarm is drawn back on Overfill, which is still there, and
+    // Inspection on the removed Corrosion.
+    assert_eq!(svg.matches(r#"class="barrier barrier-removed""#).count(), 2);
+    assert_eq!(svg.matches(r#"class="barrier barrier-added""#).count(), 1);
+}
+
+#[test]
+fn render_diff_of_unchanged_diagrams_marks_nothing() {
+    let svg = render_diff(PREVIOUS, PREVIOUS);
+    assert!(!svg.contains("-added"));
+    assert!(!svg.contains("-removed"));
+}
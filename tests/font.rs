@@ -0,0 +1,61 @@
+use bowtie::renderer::RawSvgRenderer;
+use bowtie::{generate_bowtie, generate_bowtie_with_options, FontSizes, Options};
+
+const INPUT: &str = "\
+title Spill
+cause Overfill
+consequence Injury
+event Loss of containment
+barrier Level alarm: Overfill
+";
+
+fn options() -> Options {
+    Options {
+        font_sizes: FontSizes {
+            component: 40.0,
+            ..FontSizes::default()
+        },
+        ..Options::default()
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg_font_family_applies_with_the_sizes_of_the_options() {
+    let renderer = bowtie::renderer::SvgRenderer::new().with_font("Georgia", 18.0);
+    let svg = generate_bowtie_with_options(INPUT, renderer, &options());
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.contains(r#"font-family="Georgia""#));
+    assert!(!svg.contains("Courier"));
+    assert!(svg.contains(r#"font-size="40""#));
+}
+
+#[test]
+fn raw_svg_font_family_applies_with_the_sizes_of_the_options() {
+    let renderer = RawSvgRenderer::new().with_font("Georgia", 18.0);
+    let svg = generate_bowtie_with_options(INPUT, renderer, &options());
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.contains(r#"font-family="Georgia""#));
+    assert!(!svg.contains("Courier"));
+    assert!(svg.contains(r#"font-size="40""#));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg_font_size_scales_the_sizes_of_the_layout() {
+    let renderer = bowtie::renderer::SvgRenderer::new().with_font("Georgia", 9.0);
+    let svg = String::from_utf8(generate_bowtie(INPUT, renderer)).unwrap();
+    // The title is 24 and the components 18 by default.
+    assert!(svg.contains(r#"font-size="12""#));
+    assert!(svg.contains(r#"font-size="9""#));
+    assert!(!svg.contains(r#"font-size="18""#));
+}
+
+#[test]
+fn raw_svg_font_size_scales_the_sizes_of_the_layout() {
+    let renderer = RawSvgRenderer::new().with_font("Georgia", 36.0);
+    let svg = String::from_utf8(generate_bowtie(INPUT, renderer)).unwrap();
+    assert!(svg.contains(r#"font-size="48""#));
+    assert!(svg.contains(r#"font-size="36""#));
+    assert!(!svg.contains(r#"font-size="18""#));
+}
@@ -0,0 +1,121 @@
+use bowtie::renderer::RawSvgRenderer;
+use bowtie::{
+    generate_bowtie, generate_bowtie_with_warnings, generate_tiles, render_bowtie, Diagram,
+    Options, Theme,
+};
+
+const BODY: &str = "\
+cause Overfill
+consequence Injury
+event Loss of containment
+barrier Level alarm: Overfill
+";
+
+fn with_front_matter(front_matter: &str) -> String {
+    format!("---\n{front_matter}---\n{BODY}")
+}
+
+#[test]
+fn renderer_settings_are_read() {
+    let input = with_front_matter("font_family: Georgia\nstroke_width: 5\n");
+    let options = Options::default().with_front_matter(&input).unwrap();
+    let settings = &options.renderer_settings;
+    assert_eq!(settings.font_family.as_deref(), Some("Georgia"));
+    assert_eq!(settings.stroke_width, Some(5));
+}
+
+#[test]
+fn renderer_settings_apply_to_the_render() {
+    let input = with_front_matter("font_family: Georgia\nstroke_width: 5\n");
+    let svg = generate_bowtie(&input, RawSvgRenderer::new());
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.contains(r#"font-family="Georgia""#));
+    assert!(svg.contains(r#"stroke-width="5""#));
+    assert!(!svg.contains(r#"stroke-width="3""#));
+}
+
+#[test]
+fn theme_applies_its_preset() {
+    let input = with_front_matter("theme: colorful\n");
+    let options = Options::default().with_front_matter(&input).unwrap();
+    assert!(options.color_connectors);
+    assert!(options.highlight_shared_barriers);
+    assert!(options.spine);
+    let mut plain = options.clone();
+    Theme::Plain.apply(&mut plain);
+    assert!(!plain.color_connectors && !plain.highlight_shared_barriers && !plain.spine);
+}
+
+#[test]
+fn keys_after_the_theme_override_it() {
+    let input = with_front_matter("theme: colorful\nspine: false\n");
+    let options = Options::default().with_front_matter(&input).unwrap();
+    assert!(options.color_connectors);
+    assert!(!options.spine);
+}
+
+#[test]
+fn unknown_themes_are_errors() {
+    let input = with_front_matter("theme: neon\n");
+    let error = Options::default().with_front_matter(&input).unwrap_err();
+    assert_eq!(error.line(), 2);
+    assert!(error.to_string().contains("expected `plain` or `colorful`"));
+}
+
+#[test]
+fn invalid_values_fail_the_render() {
+    let input = with_front_matter("min_event_radius: 80\nstroke_width: thick\n");
+    let mut warnings = Vec::new();
+    let error = generate_bowtie_with_warnings(
+        &input,
+        RawSvgRenderer::new(),
+        &Options::default(),
+        &mut warnings,
+    )
+    .unwrap_err();
+    assert_eq!(error.line(), 3);
+    assert_eq!(
+        error.to_string(),
+        "front matter, line 3: stroke_width: invalid value `thick`"
+    );
+    let rendered = render_bowtie(&input, RawSvgRenderer::new(), &Options::default());
+    assert_eq!(rendered.err().map(|e| e.line()), Some(3));
+    assert!(generate_tiles(&input, RawSvgRenderer::new(), 400.0, 400.0).is_err());
+}
+
+#[test]
+fn missing_closing_fences_fail_the_render() {
+    let input = format!("---\ntheme: colorful\n{BODY}");
+    let mut warnings = Vec::new();
+    let error = generate_bowtie_with_warnings(
+        &input,
+        RawSvgRenderer::new(),
+        &Options::default(),
+        &mut warnings,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "front matter, line 1: missing closing `---`"
+    );
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn unclosed_front_matter_isnt_parsed_as_commands() {
+    let input = "---\ntheme: colorful\nbarrier Level alarm: Overfill\n";
+    assert_eq!(Diagram::parse(input), Diagram::default());
+}
+
+#[cfg(all(feature = "parallel", feature = "svg"))]
+#[test]
+fn batches_fail_only_the_malformed_inputs() {
+    let malformed = with_front_matter("stroke_width: thick\n");
+    let inputs = [BODY, malformed.as_str()];
+    let outputs = bowtie::generate_many(&inputs);
+    assert!(outputs[0].is_ok());
+    assert_eq!(outputs[1].as_ref().err().map(|e| e.line()), Some(2));
+    let manifest = bowtie::generate_many_with_manifest(&inputs);
+    assert!(manifest[0].is_ok());
+    assert!(manifest[1].is_err());
+}
@@ -0,0 +1,111 @@
+use bowtie::{ComponentKind, Diagram, ParseLimits};
+
+const LIMITS: ParseLimits = ParseLimits {
+    max_line_length: 40,
+    max_components: 2,
+    max_barriers: 2,
+    max_attachments: usize::MAX,
+};
+
+#[test]
+fn input_at_the_limits_parses() {
+    let input = "\
+cause Overfill
+consequence Injury
+barrier Level alarm: Overfill
+barrier Evacuation and mustering: Injury
+";
+    assert_eq!(input.lines().map(str::len).max(), Some(40));
+    let diagram = Diagram::parse_with_limits(input, &LIMITS).unwrap();
+    assert_eq!(diagram, Diagram::parse(input));
+}
+
+#[test]
+fn lines_longer_than_the_limit_fail() {
+    let input = "\
+cause Overfill
+consequence Injury
+barrier Evacuation and mustering!: Injury
+";
+    let error = Diagram::parse_with_limits(input, &LIMITS).unwrap_err();
+    assert_eq!(error.line(), 3);
+    assert_eq!(error.to_string(), "line 3: line longer than 40 bytes");
+}
+
+#[test]
+fn components_beyond_the_limit_fail() {
+    let input = "\
+cause Overfill
+consequence Injury
+cause Corrosion
+";
+    let error = Diagram::parse_with_limits(input, &LIMITS).unwrap_err();
+    assert_eq!(error.line(), 3);
+    assert_eq!(error.to_string(), "line 3: more than 2 components");
+}
+
+#[test]
+fn barriers_beyond_the_limit_fail() {
+    let input = "\
+cause Overfill
+
+barrier Level alarm: Overfill
+barrier Relief valve: Overfill
+barrier Shutdown: Overfill
+";
+    let error = Diagram::parse_with_limits(input, &LIMITS).unwrap_err();
+    assert_eq!(error.line(), 5);
+    assert_eq!(error.to_string(), "line 5: more than 2 barriers");
+}
+
+#[test]
+fn lines_count_the_front_matter() {
+    let input = "\
+---
+spine: true
+---
+cause Overfill
+consequence Injury
+cause Corrosion
+";
+    let error = Diagram::parse_with_limits(input, &LIMITS).unwrap_err();
+    assert_eq!(error.line(), 6);
+}
+
+#[test]
+fn attachments_count_wildcards_once_expanded() {
+    let input = "\
+cause Overfill
+cause Corrosion
+barrier Level alarm: *cause, *cause, Overfill
+barrier Inspection: Corrosion, +*cause
+";
+    let limits = ParseLimits {
+        max_attachments: 4,
+        ..ParseLimits::default()
+    };
+    // The first barrier is attached twice, and the second three times.
+    let error = Diagram::parse_with_limits(input, &limits).unwrap_err();
+    assert_eq!(error.line(), 4);
+    assert_eq!(error.to_string(), "line 4: more than 4 attachments");
+}
+
+#[test]
+fn repeated_wildcards_are_expanded_once() {
+    // Lines of the default length repeating a wildcard hundreds of times,
+    // each attaching the barrier to hundreds of causes.
+    let mut input = String::new();
+    for i in 0..400 {
+        input.push_str(&format!("cause C{i}\n"));
+    }
+    let wildcards = vec!["*cause"; 500].join(", ");
+    for i in 0..50 {
+        input.push_str(&format!("barrier B{i}: {wildcards}\n"));
+    }
+    let diagram = Diagram::parse_with_limits(&input, &ParseLimits::default()).unwrap();
+    assert_eq!(diagram.barriers_for("C0", ComponentKind::Cause).len(), 50);
+    input.push_str(&format!("barrier B50: {wildcards}\n"));
+    let error = Diagram::parse_with_limits(&input, &ParseLimits::default()).unwrap_err();
+    assert_eq!(error.line(), 451);
+    assert_eq!(error.to_string(), "line 451: more than 20000 attachments");
+}
@@ -0,0 +1,103 @@
+use bowtie::{ComponentKind, Diagram, MergePolicy};
+
+const BASE: &str = "\
+title Spill
+event Loss of containment
+cause Overfill
+consequence Injury
+barrier Level alarm: Overfill
+barrier Evacuation: Injury
+";
+
+const OTHER: &str = "\
+title Leak
+event Tank rupture
+cause Overfill
+cause Corrosion
+consequence Injury
+barrier Relief valve: Overfill
+barrier Inspection: Corrosion
+barrier First aid: Injury
+";
+
+fn merge(policy: MergePolicy) -> (Diagram, bowtie::MergeReport) {
+    let mut diagram = Diagram::parse(BASE);
+    let report = diagram.merge(Diagram::parse(OTHER), policy);
+    (diagram, report)
+}
+
+#[test]
+fn skip_keeps_existing_components_and_their_barriers() {
+    let (diagram, report) = merge(MergePolicy::Skip);
+    assert_eq!(report.added, ["Corrosion"]);
+    assert!(report.merged.is_empty());
+    assert_eq!(report.skipped, ["Overfill", "Injury"]);
+    assert_eq!(
+        diagram.barriers_for("Overfill", ComponentKind::Cause),
+        ["Level alarm"]
+    );
+    assert_eq!(
+        diagram.barriers_for("Corrosion", ComponentKind::Cause),
+        ["Inspection"]
+    );
+    // The barriers of the skipped components aren't brought over.
+    assert_eq!(diagram.barrier_count(), 3);
+    assert!(diagram.validate().is_empty());
+}
+
+#[test]
+fn union_barriers_adds_the_other_components_barriers() {
+    let (diagram, report) = merge(MergePolicy::UnionBarriers);
+    assert_eq!(report.added, ["Corrosion"]);
+    assert_eq!(report.merged, ["Overfill", "Injury"]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        diagram.barriers_for("Overfill", ComponentKind::Cause),
+        ["Level alarm", "Relief valve"]
+    );
+    assert_eq!(
+        diagram.barriers_for("Injury", ComponentKind::Consequence),
+        ["Evacuation", "First aid"]
+    );
+    assert_eq!(diagram.barrier_count(), 5);
+    assert!(diagram.validate().is_empty());
+}
+
+#[test]
+fn prefer_other_replaces_components_and_drops_orphaned_barriers() {
+    let (diagram, report) = merge(MergePolicy::PreferOther);
+    assert_eq!(report.added, ["Corrosion"]);
+    assert_eq!(report.merged, ["Overfill", "Injury"]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        diagram.barriers_for("Overfill", ComponentKind::Cause),
+        ["Relief valve"]
+    );
+    assert_eq!(
+        diagram.barriers_for("Injury", ComponentKind::Consequence),
+        ["First aid"]
+    );
+    // Level alarm and Evacuation no longer protect anything.
+    assert_eq!(diagram.barrier_count(), 3);
+    assert!(diagram.validate().is_empty());
+}
+
+#[test]
+fn merge_keeps_set_values_and_fills_unset_ones() {
+    let (kept, _) = merge(MergePolicy::Skip);
+    assert!(kept
+        .to_dsl()
+        .starts_with("title Spill\nevent Loss of containment\n"));
+    let mut filled = Diagram::parse("cause Overfill\n");
+    filled.merge(Diagram::parse(OTHER), MergePolicy::Skip);
+    assert!(filled
+        .to_dsl()
+        .starts_with("title Leak\nevent Tank rupture\n"));
+}
+
+#[test]
+fn unattached_barriers_of_this_diagram_are_kept() {
+    let mut diagram = Diagram::parse("cause Overfill\nbarrier Spare:\n");
+    diagram.merge(Diagram::parse(OTHER), MergePolicy::PreferOther);
+    assert!(diagram.to_dsl().contains("barrier Spare:"));
+}
@@ -0,0 +1,163 @@
+use bowtie::{Diagram, Keywords};
+
+/// Asserts that parsing the DSL written for the diagram of the input yields
+/// an equal diagram, and that writing it again yields the same DSL.
+fn assert_round_trip(input: &str) {
+    let diagram = Diagram::parse(input);
+    let dsl = diagram.to_dsl();
+    assert_eq!(Diagram::parse(&dsl), diagram, "written as:\n{dsl}");
+    assert_eq!(Diagram::parse(&dsl).to_dsl(), dsl);
+}
+
+#[test]
+fn attributes_round_trip() {
+    assert_round_trip(
+        "\
+title Spill
+event Loss of containment | severity 4
+event_radius 80
+rating L3 S4
+cause Overfill | score 0.7 | likelihood 0.4
+cause Corrosion
+consequence Injury | likelihood 0.2
+pathway Overfill: level rises past the alarm
+describe Injury: burns from hot product
+category Process: Overfill, Corrosion
+gate Injury: Evacuation AND First aid
+route Corrosion: 10,20; 30,40
+barrier Level alarm [degraded] #alarms | effectiveness 0.9 | central: Overfill
+barrier ~Inspection [failed] ?{if tank in service}: Corrosion
+barrier Evacuation: Injury
+barrier First aid: Injury
+note Reviewed in March.
+",
+    );
+}
+
+#[test]
+fn shared_names_round_trip() {
+    assert_round_trip(
+        "\
+cause Fire
+consequence Fire
+cause Overfill
+preventive Sprinklers: Fire
+recovery Extinguishers: Fire
+barrier Level alarm: Overfill
+",
+    );
+    // Attributes both components of the name share.
+    assert_round_trip(
+        "\
+cause Fire
+consequence Fire
+gate Fire: Sprinklers AND Alarm
+category Hazards: Fire
+route Fire: 10,20
+",
+    );
+    // Attributes set before the other component of the name is declared.
+    assert_round_trip(
+        "\
+cause Fire
+icon Fire: bolt
+pathway Fire: ignition
+category Hazards: Fire
+gate Fire: Sprinklers OR Alarm
+consequence Fire
+describe Fire: spreads to the warehouse
+category Losses: Fire
+route Fire: 10,20; 30,40
+",
+    );
+}
+
+#[test]
+fn shared_attributes_are_written_once() {
+    let diagram = Diagram::parse(
+        "cause Fire\nconsequence Fire\ngate Fire: Sprinklers AND Alarm\nicon Fire: bolt\n",
+    );
+    let dsl = diagram.to_dsl();
+    assert_eq!(dsl.matches("gate Fire: Sprinklers AND Alarm").count(), 1);
+    assert_eq!(dsl.matches("icon Fire: bolt").count(), 1);
+    let diagram = Diagram::parse("cause Fire\nicon Fire: bolt\nconsequence Fire\n");
+    assert!(diagram.to_dsl().contains("icon cause Fire: bolt\n"));
+}
+
+#[test]
+fn qualified_names_prefer_components_named_like_them() {
+    let declarations = "cause Fire\nconsequence Fire\ncause cause Fire\n";
+    // `cause Fire` names the component of that name, not the cause Fire,
+    // which is named `cause cause Fire` instead.
+    let literal = Diagram::parse(&format!("{declarations}icon cause Fire: bolt\n"));
+    let qualified = Diagram::parse(&format!("{declarations}icon cause cause Fire: bolt\n"));
+    assert_eq!(literal, qualified);
+    assert_round_trip(&format!(
+        "{declarations}icon cause Fire: bolt\nicon consequence Fire: gear\n"
+    ));
+}
+
+#[test]
+fn associations_round_trip() {
+    assert_round_trip(
+        "\
+cause Overfill
+cause Corrosion
+consequence Injury
+barrier Shutdown: Overfill, +Corrosion, +Injury
+barrier Inspection: Corrosion
+",
+    );
+}
+
+#[test]
+fn out_of_order_attachments_round_trip() {
+    assert_round_trip(
+        "\
+cause Overfill
+cause Corrosion
+barrier Inspection: Corrosion
+barrier Level alarm: Overfill
+barrier Shutdown: Corrosion, Overfill
+barrier Level alarm: Corrosion
+",
+    );
+}
+
+#[test]
+fn defaults_round_trip() {
+    assert_round_trip(
+        "\
+defaults effectiveness 0.8
+cause Overfill
+barrier Level alarm: Overfill
+barrier Relief valve | effectiveness 0.5: Overfill
+",
+    );
+}
+
+#[test]
+fn keywords_round_trip() {
+    let keywords = Keywords::portuguese();
+    let input = "\
+titulo Derrame
+causa Sobrecarga
+consequencia Lesao
+barreira Alarme: Sobrecarga
+barreira Evacuacao: Lesao
+";
+    let diagram = bowtie::parse_diagram_with_keywords(input, &keywords);
+    let dsl = diagram.to_dsl_with_keywords(&keywords);
+    assert_eq!(
+        bowtie::parse_diagram_with_keywords(&dsl, &keywords),
+        diagram
+    );
+}
+
+#[test]
+fn metadata_isnt_written() {
+    let mut diagram = Diagram::parse("cause Overfill\n");
+    diagram.set_metadata("owner", "Operations");
+    let parsed = Diagram::parse(&diagram.to_dsl());
+    assert_eq!(parsed.metadata("owner"), None);
+}
@@ -0,0 +1,47 @@
+use bowtie::renderer::RawSvgRenderer;
+use bowtie::{generate_bowtie_with_warnings, Diagram, Options};
+
+fn warnings(input: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    generate_bowtie_with_warnings(
+        input,
+        RawSvgRenderer::new(),
+        &Options::default(),
+        &mut warnings,
+    )
+    .unwrap();
+    warnings.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn routes_are_read() {
+    let diagram = Diagram::parse("cause Overfill\nroute Overfill: 300,400; 350.5,-20\n");
+    assert!(diagram
+        .to_dsl()
+        .contains("route Overfill: 300,400; 350.5,-20\n"));
+}
+
+#[test]
+fn non_finite_waypoints_are_rejected() {
+    for waypoint in ["NaN,0", "0,inf", "-inf,0", "1e309,0"] {
+        let input = format!("cause Overfill\nroute Overfill: 10,20; {waypoint}\n");
+        let diagram = Diagram::parse(&input);
+        assert_eq!(diagram, diagram.clone());
+        assert!(!diagram.to_dsl().contains("route"));
+        assert_eq!(
+            warnings(&input),
+            [format!(
+                "line 2: invalid waypoint `{waypoint}`, expected one like `300,400`"
+            )]
+        );
+    }
+}
+
+#[test]
+fn malformed_waypoints_are_rejected() {
+    let input = "cause Overfill\nroute Overfill: 10,20; 30\n";
+    assert_eq!(
+        warnings(input),
+        ["line 2: invalid waypoint `30`, expected one like `300,400`"]
+    );
+}
@@ -0,0 +1,44 @@
+use bowtie::renderer::{RawSvgRenderer, Renderer};
+use bowtie::{render_bowtie, Options};
+
+const INPUT: &str = "\
+cause Overfill
+cause Corrosion
+cause Impact
+consequence Injury
+consequence Fire
+event Loss of containment
+barrier Level alarm: Overfill
+barrier Inspection: Corrosion, Impact
+barrier Evacuation: Injury, Fire
+";
+
+fn render(input: &str) -> String {
+    let options = Options {
+        color_connectors: true,
+        ..Options::default()
+    };
+    let bytes = render_bowtie(input, RawSvgRenderer::new(), &options)
+        .unwrap()
+        .into_bytes();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn colored_connectors_render_identically_across_runs() {
+    let first = render(INPUT);
+    assert!(first.contains("#0072b2"));
+    assert_eq!(first, render(INPUT));
+}
+
+#[test]
+fn connector_colors_follow_component_positions() {
+    // Renaming a component changes its label, not the colours.
+    let renamed = render(&INPUT.replace("Corrosion", "Rust"));
+    let colors = |svg: &str| {
+        svg.match_indices("stroke=\"#")
+            .map(|(i, _)| svg[i..i + 16].to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(colors(&render(INPUT)), colors(&renamed));
+}
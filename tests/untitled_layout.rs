@@ -0,0 +1,111 @@
+use bowtie::renderer::{Alignment, Icon, Rectangle, Renderer, Style, Vector2};
+use bowtie::{render_bowtie, Options};
+
+const INPUT: &str = "\
+cause Overfill
+cause Corrosion
+consequence Injury
+event Loss of containment
+barrier Level alarm: Overfill
+barrier Inspection: Corrosion
+barrier Evacuation: Injury
+";
+
+/// Records the geometry of the canvas, the event and the components.
+#[derive(Default)]
+struct Recorder {
+    canvas: (f64, f64),
+    event_centre: Option<(f64, f64)>,
+    components: Vec<(f64, f64)>,
+}
+
+impl Renderer for Recorder {
+    fn setup(mut self, width: f64, height: f64) -> Self {
+        self.canvas = (width, height);
+        self
+    }
+
+    fn draw_line(self, _from: &Vector2, _to: &Vector2, _style: &Style) -> Self {
+        self
+    }
+
+    fn draw_polyline(self, _points: &[Vector2], _style: &Style) -> Self {
+        self
+    }
+
+    fn draw_circle(mut self, _radius: f64, centre: &Vector2, style: &Style) -> Self {
+        if style.class == Some("event") {
+            self.event_centre = Some((centre.x, centre.y));
+        }
+        self
+    }
+
+    fn draw_text(self, _: &str, _: &Rectangle, _: Alignment, _: &Style) -> Self {
+        self
+    }
+
+    fn draw_rectangle(self, _rectangle: &Rectangle, _style: &Style) -> Self {
+        self
+    }
+
+    fn draw_text_with_rectangle(
+        mut self,
+        _text: &str,
+        rectangle: &Rectangle,
+        _alignment: Alignment,
+        style: &Style,
+    ) -> Self {
+        if style.class == Some("component") {
+            self.components
+                .push((rectangle.centre.x, rectangle.centre.y));
+        }
+        self
+    }
+
+    fn draw_icon(self, _icon: Icon, _rectangle: &Rectangle, _style: &Style) -> Self {
+        self
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+fn record(input: &str) -> Recorder {
+    render_bowtie(input, Recorder::default(), &Options::default()).unwrap()
+}
+
+#[test]
+fn untitled_diagram_reserves_no_title_band() {
+    let recorder = record(INPUT);
+    let (_, height) = recorder.canvas;
+    let (_, event_y) = recorder.event_centre.unwrap();
+    assert_eq!(event_y, height / 2.0);
+}
+
+#[test]
+fn untitled_diagram_layout_is_unchanged() {
+    let recorder = record(INPUT);
+    assert_eq!(recorder.canvas, (1212.5, 469.0));
+    assert_eq!(recorder.event_centre, Some((606.25, 234.5)));
+    assert_eq!(
+        recorder.components,
+        vec![(77.5, 199.5), (77.5, 269.5), (1135.0, 234.5)]
+    );
+}
+
+#[test]
+fn title_shifts_the_layout_by_its_band_only() {
+    let untitled = record(INPUT);
+    let titled = record(&format!("title Spill\n{INPUT}"));
+    let band = titled.canvas.1 - untitled.canvas.1;
+    assert!(band > 0.0);
+    let shift = |(x, y): (f64, f64)| (x, y + band);
+    assert_eq!(titled.event_centre, untitled.event_centre.map(shift));
+    let shifted = untitled
+        .components
+        .into_iter()
+        .map(shift)
+        .collect::<Vec<_>>();
+    assert_eq!(titled.components, shifted);
+}
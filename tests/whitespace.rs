@@ -0,0 +1,52 @@
+use bowtie::Diagram;
+
+const SPACED: &str = "\
+title Spill
+cause Overfill
+consequence Injury
+event Loss of containment
+barrier Level alarm | effectiveness 0.8: Overfill
+";
+
+#[test]
+fn tab_indented_lines_parse_like_spaced_ones() {
+    let input = "\
+\ttitle Spill
+\tcause Overfill
+\tconsequence Injury
+\tevent Loss of containment
+\tbarrier Level alarm | effectiveness 0.8: Overfill
+";
+    assert_eq!(Diagram::parse(input), Diagram::parse(SPACED));
+}
+
+#[test]
+fn tabs_separate_commands_from_values() {
+    let input = "\
+title\tSpill
+cause\tOverfill
+consequence\t\tInjury
+event\tLoss of containment
+barrier\tLevel alarm |\teffectiveness\t0.8: Overfill
+";
+    assert_eq!(Diagram::parse(input), Diagram::parse(SPACED));
+}
+
+#[test]
+fn multiple_spaces_separate_commands_from_values() {
+    let input = "\
+title   Spill
+  cause    Overfill
+consequence Injury
+    event  Loss of containment
+barrier   Level alarm |  effectiveness  0.8: Overfill
+";
+    let diagram = Diagram::parse(input);
+    assert_eq!(diagram, Diagram::parse(SPACED));
+    let names = diagram
+        .components()
+        .iter()
+        .map(|c| c.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Overfill", "Injury"]);
+}